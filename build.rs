@@ -1,4 +1,4 @@
-#[cfg(not(feature = "docs-only"))]
+#[cfg(all(feature = "lhapdf", not(feature = "docs-only")))]
 fn main() {
     // get LHAPDF's include directories
     let lhapdf = pkg_config::Config::new()
@@ -8,6 +8,23 @@ fn main() {
         .probe("lhapdf")
         .unwrap();
 
+    // make the version available to the crate
+    println!(
+        "cargo:rustc-env=MANAGED_LHAPDF_LHAPDF_VERSION={}",
+        lhapdf.version
+    );
+
+    if let Ok(prefix) = pkg_config::get_variable("lhapdf", "prefix") {
+        println!("cargo:rustc-env=MANAGED_LHAPDF_LHAPDF_PREFIX={prefix}");
+    }
+
+    // the evaluation of PDFs isn't thread safe in these versions, see the README
+    let mut version = lhapdf.version.split('.');
+
+    if version.next() == Some("6") && matches!(version.next(), Some("3" | "4")) {
+        println!("cargo:rustc-cfg=lhapdf_thread_unsafe");
+    }
+
     let mut build = cxx_build::bridge("src/ffi.rs");
 
     for include_path in lhapdf.include_paths {
@@ -49,3 +66,7 @@ fn main() {
     println!("cargo:rerun-if-changed=include/wrappers.hpp");
     println!("cargo:rerun-if-changed=src/ffi.rs");
 }
+
+// without LHAPDF there's no bridge to build
+#[cfg(not(feature = "lhapdf"))]
+fn main() {}
@@ -10,6 +10,7 @@
 #include <cassert>
 #include <cstddef>
 #include <map>
+#include <stdexcept>
 #include <string>
 #include <utility>
 #include <vector>
@@ -23,6 +24,11 @@ int verbosity() {
     return 0;
 }
 
+struct UserError : std::runtime_error {
+    UserError(std::string const& what) : std::runtime_error(what) {
+    }
+};
+
 struct PDFUncertainty {
     double central;
     double errplus;
@@ -38,6 +44,84 @@ struct PDFUncertainty {
     double err_par;
 };
 
+struct AlphaS {
+    virtual ~AlphaS() = default;
+
+    double alphasQ2(double) const {
+        return 0.0;
+    }
+
+    int numFlavorsQ2(double) const {
+        return 0;
+    }
+
+    double quarkMass(int) const {
+        return 0.0;
+    }
+
+    double quarkThreshold(int) const {
+        return 0.0;
+    }
+
+    int orderQCD() {
+        return 0;
+    }
+
+    void setOrderQCD(int) {
+    }
+
+    void setQuarkMass(int, double) {
+    }
+
+    void setQuarkThreshold(int, double) {
+    }
+
+    void setMZ(double) {
+    }
+
+    void setAlphaSMZ(double) {
+    }
+
+    virtual void setLambda(unsigned int, double) {
+    }
+
+    virtual std::string type() const = 0;
+};
+
+struct AlphaS_Analytic : AlphaS {
+    std::string type() const override {
+        return "analytic";
+    }
+};
+
+struct AlphaS_ODE : AlphaS {
+    std::string type() const override {
+        return "ode";
+    }
+};
+
+struct AlphaS_Ipol : AlphaS {
+    std::string type() const override {
+        return "ipol";
+    }
+
+    void setQValues(std::vector<double> const&) {
+    }
+
+    void setAlphaSValues(std::vector<double> const&) {
+    }
+};
+
+struct Info {
+    bool has_key_local(std::string const&) const {
+        return false;
+    }
+
+    std::string const& get_entry(std::string const&) const {
+        throw UserError("no metadata in fake LHAPDF");
+    }
+};
+
 struct PDFSet {
     PDFSet() = default;
 
@@ -48,6 +132,10 @@ struct PDFSet {
         return false;
     }
 
+    bool has_key_local(std::string const&) const {
+        return false;
+    }
+
     std::string const& get_entry(std::string const&) const {
         assert(false);
     }
@@ -74,6 +162,8 @@ struct PDFSet {
 };
 
 struct PDF {
+    virtual ~PDF() = default;
+
     double alphasQ2(double) const {
         return 0.0;
     }
@@ -105,6 +195,30 @@ struct PDF {
         return PDFSet();
     }
 
+    Info const& info() const {
+        return info_;
+    }
+
+    AlphaS const& alphaS() const {
+        return alphas_;
+    }
+
+    void setAlphaS(AlphaS* alphas) {
+        delete alphas;
+    }
+
+    int orderQCD() const {
+        return 0;
+    }
+
+    double quarkMass(int) const {
+        return 0.0;
+    }
+
+    double quarkThreshold(int) const {
+        return 0.0;
+    }
+
     double xMin() {
         return 0.0;
     }
@@ -113,8 +227,56 @@ struct PDF {
         return 1.0;
     }
 
+    double q2Min() {
+        return 1.0;
+    }
+
+    double q2Max() {
+        return 1e10;
+    }
+
 private:
     std::vector<int> flavors_;
+    Info info_;
+    AlphaS_Analytic alphas_;
+};
+
+struct Interpolator {
+    virtual ~Interpolator() = default;
+};
+
+struct LogBicubicInterpolator : Interpolator {};
+struct LogBilinearInterpolator : Interpolator {};
+struct BicubicInterpolator : Interpolator {};
+struct BilinearInterpolator : Interpolator {};
+struct NearestPointInterpolator : Interpolator {};
+
+struct Extrapolator {
+    virtual ~Extrapolator() = default;
+};
+
+struct ContinuationExtrapolator : Extrapolator {};
+struct NearestPointExtrapolator : Extrapolator {};
+struct ErrExtrapolator : Extrapolator {};
+
+struct GridPDF : PDF {
+    Interpolator const& interpolator() const {
+        return interpolator_;
+    }
+
+    void setInterpolator(std::string const&) {
+    }
+
+    Extrapolator const& extrapolator() const {
+        return extrapolator_;
+    }
+
+    void setExtrapolator(std::string const&) {
+    }
+
+private:
+    LogBicubicInterpolator interpolator_;
+    ContinuationExtrapolator extrapolator_;
 };
 
 std::map<int, std::string>& getPDFIndex() {
@@ -122,8 +284,22 @@ std::map<int, std::string>& getPDFIndex() {
     return pdf_index;
 }
 
+std::string findFile(std::string const&) {
+    return "";
+}
+
+void pathsPrepend(std::string const&) {}
+
+std::vector<std::string> paths() {
+    return {};
+}
+
+std::string version() {
+    return "0.0.0";
+}
+
 PDF* mkPDF(std::string const&, int) {
-    return new PDF();
+    return new GridPDF();
 }
 
 std::pair<std::string, int> lookupPDF(int) {
@@ -11,6 +11,14 @@
 #include "fake-lhapdf.hpp"
 #else
 #include <LHAPDF/LHAPDF.h>
+#include <LHAPDF/BicubicInterpolator.h>
+#include <LHAPDF/BilinearInterpolator.h>
+#include <LHAPDF/ContinuationExtrapolator.h>
+#include <LHAPDF/ErrExtrapolator.h>
+#include <LHAPDF/LogBicubicInterpolator.h>
+#include <LHAPDF/LogBilinearInterpolator.h>
+#include <LHAPDF/NearestPointExtrapolator.h>
+#include <LHAPDF/NearestPointInterpolator.h>
 #endif
 
 #include <managed-lhapdf/src/ffi.rs.h>
@@ -30,6 +38,149 @@ inline void pdf_setname(LHAPDF::PDF const& pdf, std::string& name) {
     name = pdf.set().name();
 }
 
+inline void pdf_info_entry(LHAPDF::PDF const& pdf, std::string const& key, std::string& value) {
+    value = pdf.info().get_entry(key);
+}
+
+// unlike `has_key`, this ignores the global settings in `lhapdf.conf`
+inline bool pdf_has_local_entry(LHAPDF::PDF const& pdf, std::string const& key) {
+    return pdf.info().has_key_local(key) || pdf.set().has_key_local(key);
+}
+
+inline LHAPDF::AlphaS const& pdf_alphas(LHAPDF::PDF const& pdf) {
+    return pdf.alphaS();
+}
+
+// the following methods of `PDF` aren't `const` in all versions of LHAPDF
+
+inline std::int32_t pdf_order_qcd(LHAPDF::PDF const& pdf) {
+    return const_cast<LHAPDF::PDF&>(pdf).orderQCD();
+}
+
+inline double pdf_quark_mass(LHAPDF::PDF const& pdf, std::int32_t id) {
+    return const_cast<LHAPDF::PDF&>(pdf).quarkMass(id);
+}
+
+inline double pdf_quark_threshold(LHAPDF::PDF const& pdf, std::int32_t id) {
+    return const_cast<LHAPDF::PDF&>(pdf).quarkThreshold(id);
+}
+
+inline double pdf_x_min(LHAPDF::PDF const& pdf) {
+    return const_cast<LHAPDF::PDF&>(pdf).xMin();
+}
+
+inline double pdf_x_max(LHAPDF::PDF const& pdf) {
+    return const_cast<LHAPDF::PDF&>(pdf).xMax();
+}
+
+inline double pdf_q2_min(LHAPDF::PDF const& pdf) {
+    return const_cast<LHAPDF::PDF&>(pdf).q2Min();
+}
+
+inline double pdf_q2_max(LHAPDF::PDF const& pdf) {
+    return const_cast<LHAPDF::PDF&>(pdf).q2Max();
+}
+
+inline void pdf_set_alphas(LHAPDF::PDF& pdf, std::unique_ptr<LHAPDF::AlphaS> alphas) {
+    // the PDF takes ownership of the object
+    pdf.setAlphaS(alphas.release());
+}
+
+inline std::unique_ptr<LHAPDF::AlphaS> alphas_analytic() {
+    return std::unique_ptr<LHAPDF::AlphaS>(new LHAPDF::AlphaS_Analytic());
+}
+
+inline std::unique_ptr<LHAPDF::AlphaS> alphas_ode() {
+    return std::unique_ptr<LHAPDF::AlphaS>(new LHAPDF::AlphaS_ODE());
+}
+
+inline std::unique_ptr<LHAPDF::AlphaS> alphas_ipol() {
+    return std::unique_ptr<LHAPDF::AlphaS>(new LHAPDF::AlphaS_Ipol());
+}
+
+inline void alphas_type(LHAPDF::AlphaS const& alphas, std::string& name) {
+    name = alphas.type();
+}
+
+inline std::int32_t alphas_order_qcd(LHAPDF::AlphaS const& alphas) {
+    // `orderQCD` isn't `const` in all versions of LHAPDF
+    return const_cast<LHAPDF::AlphaS&>(alphas).orderQCD();
+}
+
+inline void alphas_set_ipol_values(
+    LHAPDF::AlphaS& alphas,
+    rust::Slice<double const> q,
+    rust::Slice<double const> values
+) {
+    auto* ipol = dynamic_cast<LHAPDF::AlphaS_Ipol*>(&alphas);
+
+    if (ipol == nullptr) {
+        throw LHAPDF::UserError("values can only be set for interpolating strong couplings");
+    }
+
+    ipol->setQValues(std::vector<double>(q.begin(), q.end()));
+    ipol->setAlphaSValues(std::vector<double>(values.begin(), values.end()));
+}
+
+// interpolators and extrapolators are only defined for PDFs that are based on grids, which are all
+// PDFs that LHAPDF loads from files
+inline LHAPDF::GridPDF const& grid_pdf(LHAPDF::PDF const& pdf) {
+    auto const* grid = dynamic_cast<LHAPDF::GridPDF const*>(&pdf);
+
+    if (grid == nullptr) {
+        throw LHAPDF::UserError("PDF is not a grid PDF");
+    }
+
+    return *grid;
+}
+
+inline LHAPDF::GridPDF& grid_pdf(LHAPDF::PDF& pdf) {
+    return const_cast<LHAPDF::GridPDF&>(grid_pdf(static_cast<LHAPDF::PDF const&>(pdf)));
+}
+
+inline void pdf_interpolator(LHAPDF::PDF const& pdf, std::string& name) {
+    auto const* ipol = &grid_pdf(pdf).interpolator();
+
+    // LHAPDF doesn't store the name of the interpolator, so we infer it from its type and return
+    // the name its factory accepts
+    if (dynamic_cast<LHAPDF::LogBicubicInterpolator const*>(ipol) != nullptr) {
+        name = "logcubic";
+    } else if (dynamic_cast<LHAPDF::LogBilinearInterpolator const*>(ipol) != nullptr) {
+        name = "log";
+    } else if (dynamic_cast<LHAPDF::BicubicInterpolator const*>(ipol) != nullptr) {
+        name = "cubic";
+    } else if (dynamic_cast<LHAPDF::BilinearInterpolator const*>(ipol) != nullptr) {
+        name = "linear";
+    } else if (dynamic_cast<LHAPDF::NearestPointInterpolator const*>(ipol) != nullptr) {
+        name = "nearest";
+    } else {
+        throw LHAPDF::UserError("PDF uses an unknown interpolator");
+    }
+}
+
+inline void pdf_set_interpolator(LHAPDF::PDF& pdf, std::string const& name) {
+    grid_pdf(pdf).setInterpolator(name);
+}
+
+inline void pdf_extrapolator(LHAPDF::PDF const& pdf, std::string& name) {
+    auto const* xpol = &grid_pdf(pdf).extrapolator();
+
+    // LHAPDF doesn't store the name of the extrapolator, so we infer it from its type
+    if (dynamic_cast<LHAPDF::ContinuationExtrapolator const*>(xpol) != nullptr) {
+        name = "continuation";
+    } else if (dynamic_cast<LHAPDF::NearestPointExtrapolator const*>(xpol) != nullptr) {
+        name = "nearest";
+    } else if (dynamic_cast<LHAPDF::ErrExtrapolator const*>(xpol) != nullptr) {
+        name = "error";
+    } else {
+        throw LHAPDF::UserError("PDF uses an unknown extrapolator");
+    }
+}
+
+inline void pdf_set_extrapolator(LHAPDF::PDF& pdf, std::string const& name) {
+    grid_pdf(pdf).setExtrapolator(name);
+}
+
 inline std::unique_ptr<LHAPDF::PDF> pdf_with_setname_and_member(
     std::string const& setname,
     std::int32_t member
@@ -45,6 +196,18 @@ inline void pdfset_setname(LHAPDF::PDFSet const& pdfset, std::string& name) {
     name = pdfset.name();
 }
 
+inline void find_file(std::string const& target, std::string& path) {
+    path = LHAPDF::findFile(target);
+}
+
+inline std::unique_ptr<std::vector<std::string>> search_paths() {
+    return std::unique_ptr<std::vector<std::string>>(new std::vector<std::string>(LHAPDF::paths()));
+}
+
+inline void lhapdf_version(std::string& version) {
+    version = LHAPDF::version();
+}
+
 inline void lookup_pdf_setname(std::int32_t lhaid, std::string& setname) {
     setname = LHAPDF::lookupPDF(lhaid).first;
 }
@@ -0,0 +1,249 @@
+//! Access to the strong coupling of PDFs and standalone strong-coupling objects.
+
+use super::ffi;
+use super::{Error, Result};
+use cxx::{UniquePtr, let_cxx_string};
+use std::fmt::{self, Formatter};
+
+/// Mass of the Z boson that LHAPDF uses by default.
+pub const DEFAULT_MZ: f64 = 91.1876;
+
+/// Method used to calculate the strong coupling.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AlphaSType {
+    /// Analytic approximation of the running coupling, see LHAPDF's `AlphaS_Analytic`.
+    Analytic,
+    /// Numerical solution of the renormalization group equation, see LHAPDF's `AlphaS_ODE`.
+    Ode,
+    /// Interpolation of tabulated values, see LHAPDF's `AlphaS_Ipol`.
+    Ipol,
+}
+
+/// Borrowed view of a strong-coupling object, for instance the one of a [`Pdf`].
+///
+/// [`Pdf`]: crate::Pdf
+pub struct AlphaSRef<'a> {
+    ptr: &'a ffi::AlphaS,
+    mz: f64,
+}
+
+/// Standalone strong-coupling object, which can be evaluated on its own or attached to a PDF
+/// with [`Pdf::set_alphas`].
+///
+/// [`Pdf::set_alphas`]: crate::Pdf::set_alphas
+pub struct AlphaS {
+    ptr: UniquePtr<ffi::AlphaS>,
+    mz: f64,
+}
+
+impl fmt::Debug for AlphaSRef<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("AlphaS")
+            .field("type", &self.alphas_type())
+            .field("order_qcd", &self.order_qcd())
+            .finish()
+    }
+}
+
+impl fmt::Debug for AlphaS {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.view().fmt(f)
+    }
+}
+
+impl<'a> AlphaSRef<'a> {
+    pub(crate) const fn new(ptr: &'a ffi::AlphaS, mz: f64) -> Self {
+        Self { ptr, mz }
+    }
+
+    /// Return the method used to calculate the strong coupling.
+    ///
+    /// # Panics
+    ///
+    /// If LHAPDF reports a type not known to this crate this method panics.
+    #[must_use]
+    pub fn alphas_type(&self) -> AlphaSType {
+        let_cxx_string!(name = "");
+        ffi::alphas_type(self.ptr, name.as_mut());
+
+        match name.to_str() {
+            Ok("analytic") => AlphaSType::Analytic,
+            Ok("ode") => AlphaSType::Ode,
+            Ok("ipol") => AlphaSType::Ipol,
+            _ => panic!("unknown AlphaS type '{}'", name.to_string_lossy()),
+        }
+    }
+
+    /// Return the perturbative order of the QCD running, where 0 is leading order.
+    #[must_use]
+    pub fn order_qcd(&self) -> i32 {
+        ffi::alphas_order_qcd(self.ptr)
+    }
+
+    /// Value of the strong coupling at `q2`.
+    ///
+    /// # Errors
+    ///
+    /// If `q2` is outside the allowed range an error is returned.
+    pub fn alphas_q2(&self, q2: f64) -> Result<f64> {
+        Ok(super::serialized(|| self.ptr.alphasQ2(q2))?)
+    }
+
+    /// Value of the strong coupling at the mass of the Z boson.
+    ///
+    /// # Errors
+    ///
+    /// If the coupling can't be evaluated at the mass of the Z boson an error is returned.
+    pub fn alphas_mz(&self) -> Result<f64> {
+        self.alphas_q2(self.mz * self.mz)
+    }
+
+    /// Return the number of active flavours at `q2`.
+    ///
+    /// # Errors
+    ///
+    /// If the quark masses or thresholds needed to determine the number of flavours are not set an
+    /// error is returned.
+    pub fn num_flavors_q2(&self, q2: f64) -> Result<i32> {
+        Ok(self.ptr.numFlavorsQ2(q2)?)
+    }
+
+    /// Return the mass of the quark with PDG ID `pid`.
+    ///
+    /// # Errors
+    ///
+    /// If `pid` isn't a quark or its mass is not set an error is returned.
+    pub fn quark_mass(&self, pid: i32) -> Result<f64> {
+        Ok(self.ptr.quarkMass(pid)?)
+    }
+
+    /// Return the flavour threshold of the quark with PDG ID `pid`.
+    ///
+    /// # Errors
+    ///
+    /// If `pid` isn't a quark or its threshold is not set an error is returned.
+    pub fn quark_threshold(&self, pid: i32) -> Result<f64> {
+        Ok(self.ptr.quarkThreshold(pid)?)
+    }
+}
+
+impl AlphaS {
+    const fn new(ptr: UniquePtr<ffi::AlphaS>) -> Self {
+        Self {
+            ptr,
+            mz: DEFAULT_MZ,
+        }
+    }
+
+    /// Create a strong coupling that uses an analytic approximation. The values of Lambda must be
+    /// set with [`AlphaS::set_lambda`].
+    #[must_use]
+    pub fn analytic() -> Self {
+        Self::new(ffi::alphas_analytic())
+    }
+
+    /// Create a strong coupling that solves the renormalization group equation numerically,
+    /// starting from the value set with [`AlphaS::set_alphas_mz`].
+    #[must_use]
+    pub fn ode() -> Self {
+        Self::new(ffi::alphas_ode())
+    }
+
+    /// Create a strong coupling that interpolates the values set with
+    /// [`AlphaS::set_ipol_values`].
+    #[must_use]
+    pub fn ipol() -> Self {
+        Self::new(ffi::alphas_ipol())
+    }
+
+    /// Return a view of this object that gives access to its properties.
+    #[must_use]
+    pub fn view(&self) -> AlphaSRef<'_> {
+        // UNWRAP: the pointer is created by our constructors and never null
+        AlphaSRef::new(self.ptr.as_ref().unwrap_or_else(|| unreachable!()), self.mz)
+    }
+
+    /// Value of the strong coupling at `q2`.
+    ///
+    /// # Errors
+    ///
+    /// If `q2` is outside the allowed range an error is returned.
+    pub fn alphas_q2(&self, q2: f64) -> Result<f64> {
+        self.view().alphas_q2(q2)
+    }
+
+    /// Set the perturbative order of the QCD running, where 0 is leading order.
+    pub fn set_order_qcd(&mut self, order: i32) {
+        self.ptr.pin_mut().setOrderQCD(order);
+    }
+
+    /// Set the mass of the quark with PDG ID `pid`.
+    ///
+    /// # Errors
+    ///
+    /// If `pid` isn't a quark an error is returned.
+    pub fn set_quark_mass(&mut self, pid: i32, mass: f64) -> Result<()> {
+        Ok(self.ptr.pin_mut().setQuarkMass(pid, mass)?)
+    }
+
+    /// Set the flavour threshold of the quark with PDG ID `pid`.
+    ///
+    /// # Errors
+    ///
+    /// If `pid` isn't a quark an error is returned.
+    pub fn set_quark_threshold(&mut self, pid: i32, threshold: f64) -> Result<()> {
+        Ok(self.ptr.pin_mut().setQuarkThreshold(pid, threshold)?)
+    }
+
+    /// Set the mass of the Z boson.
+    pub fn set_mz(&mut self, mz: f64) {
+        self.mz = mz;
+        self.ptr.pin_mut().setMZ(mz);
+    }
+
+    /// Set the value of the strong coupling at the mass of the Z boson.
+    pub fn set_alphas_mz(&mut self, alphas: f64) {
+        self.ptr.pin_mut().setAlphaSMZ(alphas);
+    }
+
+    /// Set the value of Lambda for `nf` active flavours. Only strong couplings created with
+    /// [`AlphaS::analytic`] use these values.
+    ///
+    /// # Errors
+    ///
+    /// If this isn't an analytic strong coupling an error is returned.
+    pub fn set_lambda(&mut self, nf: u32, lambda: f64) -> Result<()> {
+        if self.view().alphas_type() != AlphaSType::Analytic {
+            return Err(Error::General(
+                "Lambda can only be set for analytic strong couplings".to_owned(),
+            ));
+        }
+
+        self.ptr.pin_mut().setLambda(nf, lambda);
+
+        Ok(())
+    }
+
+    /// Set the knots `q` and the values `alphas` that are interpolated. Only strong couplings
+    /// created with [`AlphaS::ipol`] use these values.
+    ///
+    /// # Errors
+    ///
+    /// If this isn't an interpolating strong coupling or `q` and `alphas` have different lengths
+    /// an error is returned.
+    pub fn set_ipol_values(&mut self, q: &[f64], alphas: &[f64]) -> Result<()> {
+        if q.len() != alphas.len() {
+            return Err(Error::General(format!(
+                "number of knots ({}) and values ({}) differ",
+                q.len(),
+                alphas.len()
+            )));
+        }
+
+        Ok(ffi::alphas_set_ipol_values(self.ptr.pin_mut(), q, alphas)?)
+    }
+
+    pub(crate) fn into_ptr(self) -> UniquePtr<ffi::AlphaS> {
+        self.ptr
+    }
+}
@@ -0,0 +1,159 @@
+/// Common interface of the different implementations of a PDF member, so that code evaluating PDFs
+/// doesn't depend on whether LHAPDF or the pure-Rust implementation does the work.
+///
+/// [`Pdf`](super::Pdf) implements this trait by calling LHAPDF, and with the `native` feature
+/// [`NativePdf`](super::NativePdf) implements it without LHAPDF.
+pub trait PdfBackend {
+    /// Get the PDF `x * f(x)` value at `x` and `q2` for the given PDG ID.
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    fn xfx_q2(&self, id: i32, x: f64, q2: f64) -> f64;
+
+    /// Value of the strong coupling at `q2` used by this PDF.
+    ///
+    /// # Panics
+    ///
+    /// If the value of `q2` is not within proper boundaries this method will panic.
+    fn alphas_q2(&self, q2: f64) -> f64;
+
+    /// List of flavours defined by this PDF.
+    fn flavors(&self) -> Vec<i32>;
+
+    /// Minimum valid x value for this PDF.
+    fn x_min(&self) -> f64;
+
+    /// Maximum valid x value for this PDF.
+    fn x_max(&self) -> f64;
+
+    /// Minimum valid `Q^2` value for this PDF.
+    fn q2_min(&self) -> f64;
+
+    /// Maximum valid `Q^2` value for this PDF.
+    fn q2_max(&self) -> f64;
+
+    /// Derivative of `x * f(x)` with respect to `x` at `x` and `q2` for the given PDG ID.
+    ///
+    /// The default implementation uses finite differences of [`PdfBackend::xfx_q2`].
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    fn dxfx_dx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        dxfx_dx(self, id, x, q2)
+    }
+
+    /// Derivative of `x * f(x)` with respect to `ln(Q^2)` at `x` and `q2` for the given PDG ID.
+    ///
+    /// The default implementation uses finite differences of [`PdfBackend::xfx_q2`].
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    fn dxfx_dlnq2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        dxfx_dlnq2(self, id, x, q2)
+    }
+}
+
+/// Step in `ln(x)` and `ln(Q^2)` of the finite differences, which balances the truncation error
+/// against the rounding error.
+const STEP: f64 = 1e-4;
+
+/// Derivative of `x * f(x)` of `pdf` with respect to `x`, calculated with finite differences.
+pub fn dxfx_dx<P: PdfBackend + ?Sized>(pdf: &P, id: i32, x: f64, q2: f64) -> f64 {
+    let (lower, upper) = region(x.ln(), pdf.x_min().ln(), pdf.x_max().ln());
+
+    differentiate(|logx| pdf.xfx_q2(id, logx.exp(), q2), x.ln(), lower, upper) / x
+}
+
+/// Derivative of `x * f(x)` of `pdf` with respect to `ln(Q^2)`, calculated with finite
+/// differences.
+pub fn dxfx_dlnq2<P: PdfBackend + ?Sized>(pdf: &P, id: i32, x: f64, q2: f64) -> f64 {
+    let (lower, upper) = region(q2.ln(), pdf.q2_min().ln(), pdf.q2_max().ln());
+
+    differentiate(
+        |logq2| pdf.xfx_q2(id, x, logq2.exp()),
+        q2.ln(),
+        lower,
+        upper,
+    )
+}
+
+/// Return the interval in which `value` lies: below `min`, between `min` and `max`, or above
+/// `max`. Inside of the grid PDFs are interpolated and outside extrapolated, and the finite
+/// differences shouldn't mix both.
+fn region(value: f64, min: f64, max: f64) -> (f64, f64) {
+    if value < min {
+        (f64::NEG_INFINITY, min)
+    } else if value > max {
+        (max, f64::INFINITY)
+    } else {
+        (min, max)
+    }
+}
+
+/// Differentiate `f` at `y` with finite differences of second order, whose points are within
+/// `[lower, upper]`.
+fn differentiate(f: impl Fn(f64) -> f64, y: f64, lower: f64, upper: f64) -> f64 {
+    let (h, h2) = (STEP, 2.0 * STEP);
+
+    if y - h >= lower && y + h <= upper {
+        (f(y + h) - f(y - h)) / h2
+    } else if y + h2 <= upper {
+        (4.0f64.mul_add(f(y + h), -3.0 * f(y)) - f(y + h2)) / h2
+    } else {
+        (3.0f64.mul_add(f(y), -4.0 * f(y - h)) + f(y - h2)) / h2
+    }
+}
+
+/// PDF for unit tests, which evaluates `xfx` for the partons in `flavors` on a grid covering
+/// `x_min <= x <= 1` and `1 <= Q^2 <= 1e8`, with a constant strong coupling.
+#[cfg(test)]
+pub struct Toy<F> {
+    flavors: Vec<i32>,
+    x_min: f64,
+    xfx: F,
+}
+
+#[cfg(test)]
+impl<F: Fn(i32, f64, f64) -> f64> Toy<F> {
+    pub const fn new(flavors: Vec<i32>, x_min: f64, xfx: F) -> Self {
+        Self {
+            flavors,
+            x_min,
+            xfx,
+        }
+    }
+}
+
+#[cfg(test)]
+impl<F: Fn(i32, f64, f64) -> f64> PdfBackend for Toy<F> {
+    fn xfx_q2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        (self.xfx)(id, x, q2)
+    }
+
+    fn alphas_q2(&self, _: f64) -> f64 {
+        0.118
+    }
+
+    fn flavors(&self) -> Vec<i32> {
+        self.flavors.clone()
+    }
+
+    fn x_min(&self) -> f64 {
+        self.x_min
+    }
+
+    fn x_max(&self) -> f64 {
+        1.0
+    }
+
+    fn q2_min(&self) -> f64 {
+        1.0
+    }
+
+    fn q2_max(&self) -> f64 {
+        1e8
+    }
+}
@@ -0,0 +1,270 @@
+//! Rotations between the flavour basis of PDG IDs and the evolution basis.
+//!
+//! Both bases have [`SIZE`] elements. The PDG basis is ordered as in [`PIDS`], with the photon
+//! first followed by the anti-quarks, the gluon and the quarks. The evolution basis is ordered as
+//! in [`EVOLUTION_LABELS`], where with `q± = q ± qbar`
+//!
+//! - `Σ = Σ_q q+` is the singlet and `V = Σ_q q-` the total valence distribution,
+//! - `V3 = u- - d-`, `V8 = u- + d- - 2 s-`, up to `V35 = u- + d- + s- + c- + b- - 5 t-` are the
+//!   non-singlet valence distributions, and
+//! - `T3`, `T8`, up to `T35` are the corresponding non-singlet distributions of `q+`.
+//!
+//! The photon and the gluon are the same in both bases. LHAPDF accepts `0` as PDG ID of the gluon,
+//! which is treated like `21`.
+
+use super::backend::PdfBackend;
+use super::{Error, Result};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Number of elements of both bases.
+pub const SIZE: usize = 14;
+
+/// PDG IDs of the elements of the PDG basis.
+pub const PIDS: [i32; SIZE] = [22, -6, -5, -4, -3, -2, -1, 21, 1, 2, 3, 4, 5, 6];
+
+/// Names of the elements of the evolution basis.
+pub const EVOLUTION_LABELS: [&str; SIZE] = [
+    "photon", "Sigma", "g", "V", "V3", "V8", "V15", "V24", "V35", "T3", "T8", "T15", "T24", "T35",
+];
+
+/// PDG IDs of the quarks in the order in which they enter the non-singlet distributions.
+const QUARKS: [i32; 6] = [2, 1, 3, 4, 5, 6];
+
+/// Basis in which PDF values are given.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Basis {
+    /// Photon, anti-quarks, gluon and quarks, ordered as in [`PIDS`].
+    #[default]
+    Pdg,
+    /// Photon, singlet, gluon, and the valence and non-singlet distributions, ordered as in
+    /// [`EVOLUTION_LABELS`].
+    Evolution,
+}
+
+impl Basis {
+    /// Return the names of the elements of this basis.
+    #[must_use]
+    pub fn labels(self) -> Vec<String> {
+        match self {
+            Self::Pdg => PIDS.iter().map(ToString::to_string).collect(),
+            Self::Evolution => EVOLUTION_LABELS
+                .iter()
+                .map(|&label| label.to_owned())
+                .collect(),
+        }
+    }
+
+    /// Return the matrix that rotates a vector given in the PDG basis into this basis.
+    #[must_use]
+    pub fn from_pdg(self) -> [[f64; SIZE]; SIZE] {
+        match self {
+            Self::Pdg => identity(),
+            Self::Evolution => pdg_to_evolution(),
+        }
+    }
+
+    /// Return the matrix that rotates a vector given in this basis into the PDG basis.
+    #[must_use]
+    pub fn to_pdg(self) -> [[f64; SIZE]; SIZE] {
+        match self {
+            Self::Pdg => identity(),
+            Self::Evolution => evolution_to_pdg(),
+        }
+    }
+}
+
+impl Display for Basis {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Pdg => write!(f, "pdg"),
+            Self::Evolution => write!(f, "evolution"),
+        }
+    }
+}
+
+impl FromStr for Basis {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "pdg" => Ok(Self::Pdg),
+            "evolution" => Ok(Self::Evolution),
+            _ => Err(Error::General(format!("unknown basis '{s}'"))),
+        }
+    }
+}
+
+/// Return `21` for the alternative PDG ID `0` of the gluon, and `pid` otherwise.
+#[must_use]
+pub const fn canonical_pid(pid: i32) -> i32 {
+    if pid == 0 { 21 } else { pid }
+}
+
+/// Return the matrix that rotates a vector in the PDG basis into the evolution basis.
+#[must_use]
+pub fn pdg_to_evolution() -> [[f64; SIZE]; SIZE] {
+    let mut matrix = [[0.0; SIZE]; SIZE];
+    let column = |pid: i32| {
+        // UNWRAP: only called with the PDG IDs of the basis
+        PIDS.iter()
+            .position(|&p| p == pid)
+            .unwrap_or_else(|| unreachable!())
+    };
+
+    matrix[0][column(22)] = 1.0;
+    matrix[2][column(21)] = 1.0;
+
+    for pid in QUARKS {
+        matrix[1][column(pid)] = 1.0;
+        matrix[1][column(-pid)] = 1.0;
+        matrix[3][column(pid)] = 1.0;
+        matrix[3][column(-pid)] = -1.0;
+    }
+
+    // `V3` to `V35` are in rows 4 to 8, and `T3` to `T35` in rows 9 to 13
+    for (index, &pid) in QUARKS.iter().enumerate().skip(1) {
+        let (valence, nonsinglet) = (3 + index, 8 + index);
+
+        for &lighter in &QUARKS[..index] {
+            matrix[valence][column(lighter)] = 1.0;
+            matrix[valence][column(-lighter)] = -1.0;
+            matrix[nonsinglet][column(lighter)] = 1.0;
+            matrix[nonsinglet][column(-lighter)] = 1.0;
+        }
+
+        // UNWRAP: `index` is at most five
+        let weight = -f64::from(u8::try_from(index).unwrap_or_else(|_| unreachable!()));
+        matrix[valence][column(pid)] = weight;
+        matrix[valence][column(-pid)] = -weight;
+        matrix[nonsinglet][column(pid)] = weight;
+        matrix[nonsinglet][column(-pid)] = weight;
+    }
+
+    matrix
+}
+
+/// Return the matrix that rotates a vector in the evolution basis into the PDG basis, which is
+/// the inverse of [`pdg_to_evolution`].
+#[must_use]
+pub fn evolution_to_pdg() -> [[f64; SIZE]; SIZE] {
+    let rotation = pdg_to_evolution();
+    let mut matrix = [[0.0; SIZE]; SIZE];
+
+    // the rows of the rotation are orthogonal, so its inverse is the transpose with each column
+    // divided by the squared norm of the corresponding row
+    for (row, values) in rotation.iter().enumerate() {
+        let norm: f64 = values.iter().map(|value| value * value).sum();
+
+        for (column, value) in values.iter().enumerate() {
+            matrix[column][row] = value / norm;
+        }
+    }
+
+    matrix
+}
+
+/// Multiply `matrix` with `vector`.
+#[must_use]
+pub fn rotate(matrix: &[[f64; SIZE]; SIZE], vector: &[f64; SIZE]) -> [f64; SIZE] {
+    matrix.map(|row| row.iter().zip(vector).map(|(lhs, rhs)| lhs * rhs).sum())
+}
+
+/// Evaluate `x * f(x)` of all elements of `basis` for `pdf` at `x` and `q2`. Flavours that `pdf`
+/// doesn't define, for instance the photon or the top quark, are zero.
+///
+/// # Panics
+///
+/// If the value of either `x` or `q2` is not within proper boundaries this function will panic.
+pub fn xfx_q2<P: PdfBackend + ?Sized>(pdf: &P, basis: Basis, x: f64, q2: f64) -> [f64; SIZE] {
+    let flavors = pdf.flavors();
+    let pdg = PIDS.map(|pid| {
+        flavors
+            .iter()
+            .find(|&&flavor| canonical_pid(flavor) == pid)
+            .map_or(0.0, |&flavor| pdf.xfx_q2(flavor, x, q2))
+    });
+
+    match basis {
+        Basis::Pdg => pdg,
+        Basis::Evolution => rotate(&pdg_to_evolution(), &pdg),
+    }
+}
+
+fn identity() -> [[f64; SIZE]; SIZE] {
+    let mut matrix = [[0.0; SIZE]; SIZE];
+
+    for (index, row) in matrix.iter_mut().enumerate() {
+        row[index] = 1.0;
+    }
+
+    matrix
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::backend::Toy;
+    use super::*;
+
+    #[test]
+    fn rotations_are_inverse() {
+        let product = rotate(
+            &evolution_to_pdg(),
+            &rotate(&pdg_to_evolution(), &PIDS.map(f64::from)),
+        );
+
+        for (lhs, rhs) in product.iter().zip(PIDS) {
+            assert!((lhs - f64::from(rhs)).abs() < 1e-14);
+        }
+
+        assert_eq!(Basis::Evolution.from_pdg(), pdg_to_evolution());
+        assert_eq!(Basis::Pdg.to_pdg(), identity());
+        assert_eq!("Evolution".parse::<Basis>().unwrap(), Basis::Evolution);
+        assert!("flavor".parse::<Basis>().is_err());
+    }
+
+    #[test]
+    fn evaluate_in_bases() {
+        // `x f(x) = x * pid`, with the gluon listed as `0` and without photon and top quark
+        let toy = Toy::new(
+            vec![-5, -4, -3, -2, -1, 0, 1, 2, 3, 4, 5],
+            1e-9,
+            |id, x, _| {
+                assert_ne!(id, 21);
+                x * f64::from(id)
+            },
+        );
+        let pdg = xfx_q2(&toy, Basis::Pdg, 0.5, 100.0);
+
+        // the photon and the top quark are missing, and the gluon is `0`
+        let expected = [
+            0.0, 0.0, -2.5, -2.0, -1.5, -1.0, -0.5, 0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 0.0,
+        ];
+
+        for (lhs, rhs) in pdg.iter().zip(expected) {
+            assert!((lhs - rhs).abs() < 1e-14, "{lhs} != {rhs}");
+        }
+
+        let evolution = xfx_q2(&toy, Basis::Evolution, 0.5, 100.0);
+        let labels = Basis::Evolution.labels();
+        let value = |label: &str| evolution[labels.iter().position(|l| l == label).unwrap()];
+        let assert_value = |label: &str, expected: f64| {
+            assert!(
+                (value(label) - expected).abs() < 1e-14,
+                "{label} = {} != {expected}",
+                value(label)
+            );
+        };
+
+        // `q+` vanishes and `q- = x * pid`
+        assert_value("Sigma", 0.0);
+        assert_value("T8", 0.0);
+        assert_value("V", 15.0);
+        // `V3 = u- - d- = 2 - 1`
+        assert_value("V3", 1.0);
+        // `V8 = u- + d- - 2 s- = 2 + 1 - 6`
+        assert_value("V8", -3.0);
+        // `V35 = u- + d- + s- + c- + b- - 5 t-` with the missing top quark
+        assert_value("V35", 15.0);
+    }
+}
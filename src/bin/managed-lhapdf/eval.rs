@@ -0,0 +1,213 @@
+//! Subcommands that evaluate PDFs and the strong coupling.
+
+use anyhow::{Result, anyhow};
+use clap::{Args, ValueEnum};
+use managed_lhapdf::{self as lhapdf, CL_1_SIGMA, Pdf, PdfSet};
+use serde_json::{Map, Value};
+
+/// Format in which tables are printed.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Columns aligned for reading.
+    Text,
+    /// Comma-separated values with a header line.
+    Csv,
+    /// An array with one object per row.
+    Json,
+}
+
+/// Options shared by the subcommands that evaluate PDFs.
+#[derive(Args)]
+pub struct EvalOpts {
+    /// Format of the printed table.
+    #[arg(default_value = "text", long, value_enum)]
+    format: Format,
+    /// Evaluate every member and print the central value and the uncertainties of the set.
+    #[arg(long)]
+    uncertainty: bool,
+    /// Confidence level of the uncertainties in percent.
+    #[arg(default_value_t = CL_1_SIGMA, long, requires = "uncertainty")]
+    cl: f64,
+}
+
+/// Either a single member, or all members of a set together with the set to calculate the
+/// uncertainties.
+enum Members {
+    Single(Pdf),
+    All(PdfSet, Vec<Pdf>, f64),
+}
+
+impl Members {
+    /// Load the PDF given by `pdf`, which is either an LHAID or has the form `<set>/<member>`, or
+    /// all members of its set if uncertainties are requested.
+    fn new(pdf: &str, opts: &EvalOpts) -> Result<Self> {
+        let lhaid = pdf.parse().ok();
+
+        if opts.uncertainty {
+            let setname = match lhaid {
+                Some(lhaid) => {
+                    lhapdf::lookup_pdf(lhaid)
+                        .ok_or_else(|| anyhow!("LHAID {lhaid} was not found in pdfsets.index"))?
+                        .0
+                }
+                None => pdf
+                    .split_once('/')
+                    .map_or(pdf, |(setname, _)| setname)
+                    .to_owned(),
+            };
+            let set = PdfSet::new(&setname)?;
+            let pdfs = set.mk_pdfs()?;
+
+            Ok(Self::All(set, pdfs, opts.cl))
+        } else {
+            Ok(Self::Single(lhaid.map_or_else(
+                || Pdf::with_setname_and_nmem(pdf),
+                Pdf::with_lhaid,
+            )?))
+        }
+    }
+
+    /// Return the names of the columns holding the results of [`Members::eval`].
+    fn columns(&self, name: &'static str) -> Vec<&'static str> {
+        match self {
+            Self::Single(_) => vec![name],
+            Self::All(..) => vec![name, "errminus", "errplus"],
+        }
+    }
+
+    fn eval(&self, f: impl Fn(&Pdf) -> f64) -> Result<Vec<Value>> {
+        match self {
+            Self::Single(pdf) => Ok(vec![f(pdf).into()]),
+            Self::All(set, pdfs, cl) => {
+                let values: Vec<_> = pdfs.iter().map(f).collect();
+                let uncertainty = set.uncertainty(&values, *cl, false)?;
+
+                Ok(vec![
+                    uncertainty.central.into(),
+                    uncertainty.errminus.into(),
+                    uncertainty.errplus.into(),
+                ])
+            }
+        }
+    }
+}
+
+struct Table {
+    header: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn print(self, format: Format) {
+        match format {
+            Format::Text => {
+                let rows: Vec<Vec<_>> = self
+                    .rows
+                    .iter()
+                    .map(|row| row.iter().map(ToString::to_string).collect())
+                    .collect();
+                let widths: Vec<_> = self
+                    .header
+                    .iter()
+                    .enumerate()
+                    .map(|(column, name)| {
+                        rows.iter()
+                            .map(|row| row[column].len())
+                            .fold(name.len(), usize::max)
+                    })
+                    .collect();
+                let print_row = |row: &[&str]| {
+                    let line: Vec<_> = row
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, &width)| format!("{cell:>width$}"))
+                        .collect();
+                    println!("{}", line.join("  "));
+                };
+
+                print_row(&self.header);
+
+                for row in &rows {
+                    print_row(&row.iter().map(String::as_str).collect::<Vec<_>>());
+                }
+            }
+            Format::Csv => {
+                println!("{}", self.header.join(","));
+
+                for row in self.rows {
+                    let row: Vec<_> = row.iter().map(ToString::to_string).collect();
+                    println!("{}", row.join(","));
+                }
+            }
+            Format::Json => {
+                let rows: Vec<_> = self
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        Value::Object(
+                            self.header
+                                .iter()
+                                .map(|&name| name.to_owned())
+                                .zip(row)
+                                .collect::<Map<_, _>>(),
+                        )
+                    })
+                    .collect();
+                println!("{}", Value::Array(rows));
+            }
+        }
+    }
+}
+
+const fn format(opts: &EvalOpts, json: bool) -> Format {
+    if json { Format::Json } else { opts.format }
+}
+
+/// Print `x * f(x, Q^2)` of `pdf` for every combination of `pids`, `xs` and `qs`.
+pub fn eval(
+    pdf: &str,
+    pids: &[i32],
+    xs: &[f64],
+    qs: &[f64],
+    opts: &EvalOpts,
+    json: bool,
+) -> Result<()> {
+    let members = Members::new(pdf, opts)?;
+    let mut table = Table {
+        header: [vec!["pid", "x", "q"], members.columns("xfx")].concat(),
+        rows: Vec::new(),
+    };
+
+    for &pid in pids {
+        for &x in xs {
+            for &q in qs {
+                let values = members.eval(|pdf| pdf.xfx_q2(pid, x, q * q))?;
+                table
+                    .rows
+                    .push([vec![pid.into(), x.into(), q.into()], values].concat());
+            }
+        }
+    }
+
+    table.print(format(opts, json));
+
+    Ok(())
+}
+
+/// Print the strong coupling of `pdf` at the scales `qs`.
+pub fn alphas(pdf: &str, qs: &[f64], opts: &EvalOpts, json: bool) -> Result<()> {
+    let members = Members::new(pdf, opts)?;
+    let mut table = Table {
+        header: [vec!["q"], members.columns("alphas")].concat(),
+        rows: Vec::new(),
+    };
+
+    for &q in qs {
+        let values = members.eval(|pdf| pdf.alphas_q2(q * q))?;
+        table.rows.push([vec![q.into()], values].concat());
+    }
+
+    table.print(format(opts, json));
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+#![warn(clippy::all, clippy::cargo, clippy::nursery, clippy::pedantic)]
+
+//! Command-line tool to manage the PDF sets of `managed-lhapdf`.
+
+mod eval;
+
+use anyhow::{Result, anyhow};
+use clap::{Parser, Subcommand};
+use eval::EvalOpts;
+use managed_lhapdf::grid::Info;
+use managed_lhapdf::{self as lhapdf, Config, PdfSet};
+use serde_json::{Map, Value, json};
+
+/// Manage the PDF sets used by `managed-lhapdf`.
+#[derive(Parser)]
+#[command(version)]
+struct Opts {
+    /// Print the output as JSON.
+    #[arg(global = true, long)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Download PDF sets, given by their names or LHAIDs, unless they're already installed.
+    Install {
+        /// Names or LHAIDs of the PDF sets.
+        #[arg(required = true)]
+        sets: Vec<String>,
+    },
+    /// List the installed PDF sets or the ones listed in `pdfsets.index`.
+    List {
+        /// List the PDF sets in the local data directories, which is the default.
+        #[arg(conflicts_with = "remote", long)]
+        installed: bool,
+        /// List the PDF sets in `pdfsets.index`.
+        #[arg(long)]
+        remote: bool,
+    },
+    /// Show the metadata of a PDF set, downloading it if needed.
+    Show {
+        /// Name of the PDF set.
+        set: String,
+    },
+    /// Print the name of the PDF set and the member an LHAID refers to.
+    Lookup {
+        /// The LHAID to look up.
+        lhaid: i32,
+    },
+    /// Remove PDF sets from the download directory.
+    Remove {
+        /// Names of the PDF sets.
+        #[arg(required = true)]
+        sets: Vec<String>,
+    },
+    /// Refresh the local copy of `pdfsets.index` and print the LHAIDs its sources disagree on.
+    UpdateIndex,
+    /// Print the effective configuration.
+    Config,
+    /// Print a table of PDF values.
+    Eval {
+        /// The PDF as `<set>/<member>` or LHAID; without a member the central one is used.
+        pdf: String,
+        /// PDG IDs of the partons, separated by commas.
+        #[arg(
+            allow_negative_numbers = true,
+            long,
+            required = true,
+            value_delimiter = ','
+        )]
+        pid: Vec<i32>,
+        /// Values of x, separated by commas.
+        #[arg(long, required = true, value_delimiter = ',')]
+        x: Vec<f64>,
+        /// Scales `Q` in `GeV`, separated by commas.
+        #[arg(long, required = true, value_delimiter = ',')]
+        q: Vec<f64>,
+        #[command(flatten)]
+        opts: EvalOpts,
+    },
+    /// Print a table of the strong coupling used by a PDF.
+    Alphas {
+        /// The PDF as `<set>/<member>` or LHAID; without a member the central one is used.
+        pdf: String,
+        /// Scales `Q` in `GeV`, separated by commas.
+        #[arg(long, required = true, value_delimiter = ',')]
+        q: Vec<f64>,
+        #[command(flatten)]
+        opts: EvalOpts,
+    },
+}
+
+/// Return the name of the PDF set `set`, which is either a name or an LHAID.
+fn setname(set: &str) -> Result<String> {
+    set.parse().map_or_else(
+        |_| Ok(set.to_owned()),
+        |lhaid| {
+            lhapdf::lookup_pdf(lhaid)
+                .map(|(setname, _)| setname)
+                .ok_or_else(|| anyhow!("LHAID {lhaid} was not found in pdfsets.index"))
+        },
+    )
+}
+
+fn install(sets: &[String], json: bool) -> Result<()> {
+    let mut installed = Vec::new();
+
+    for set in sets {
+        let name = setname(set)?;
+        let path = lhapdf::install_set(&name)?;
+
+        if !json {
+            println!("{name}: {}", path.display());
+        }
+
+        installed.push(json!({ "name": name, "path": path }));
+    }
+
+    if json {
+        println!("{}", Value::Array(installed));
+    }
+
+    Ok(())
+}
+
+fn list(remote: bool, json: bool) -> Result<()> {
+    if remote {
+        let sets = lhapdf::remote_sets()?;
+
+        if json {
+            let sets: Vec<_> = sets
+                .into_iter()
+                .map(|set| {
+                    json!({ "name": set.name, "lhaid": set.lhaid, "data_version": set.data_version })
+                })
+                .collect();
+            println!("{}", Value::Array(sets));
+        } else {
+            for set in sets {
+                println!("{:>8} {}", set.lhaid, set.name);
+            }
+        }
+    } else {
+        let sets = lhapdf::installed_sets()?;
+
+        if json {
+            let sets: Vec<_> = sets
+                .into_iter()
+                .map(|set| {
+                    json!({ "name": set.name, "path": set.path, "data_version": set.data_version })
+                })
+                .collect();
+            println!("{}", Value::Array(sets));
+        } else {
+            for set in sets {
+                println!("{}", set.name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn show(set: &str, json: bool) -> Result<()> {
+    let name = setname(set)?;
+    let path = lhapdf::install_set(&name)?;
+    let pdfset = PdfSet::new(&name)?;
+    // LHAPDF doesn't list the keys of a set, so take them from the `.info` file
+    let info = Info::read(&path.join(format!("{name}.info")))?;
+    let entries: Vec<_> = info
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.to_owned(),
+                pdfset.entry(key).unwrap_or_else(|| value.to_owned()),
+            )
+        })
+        .collect();
+
+    if json {
+        let entries: Map<_, _> = entries
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect();
+        println!(
+            "{}",
+            json!({ "name": name, "path": path, "entries": entries })
+        );
+    } else {
+        println!("Path: {}", path.display());
+
+        for (key, value) in entries {
+            println!("{key}: {value}");
+        }
+    }
+
+    Ok(())
+}
+
+fn lookup(lhaid: i32, json: bool) -> Result<()> {
+    let (setname, member) = lhapdf::lookup_pdf(lhaid)
+        .ok_or_else(|| anyhow!("LHAID {lhaid} was not found in pdfsets.index"))?;
+
+    if json {
+        println!(
+            "{}",
+            json!({ "lhaid": lhaid, "set": setname, "member": member })
+        );
+    } else {
+        println!("{setname}/{member}");
+    }
+
+    Ok(())
+}
+
+fn remove(sets: &[String], json: bool) -> Result<()> {
+    let mut removed = Vec::new();
+
+    for set in sets {
+        let name = setname(set)?;
+        let result = lhapdf::remove_set(&name)?;
+
+        if !json {
+            if result {
+                println!("removed {name}");
+            } else {
+                println!("{name} is not installed in the download directory");
+            }
+        }
+
+        removed.push(json!({ "name": name, "removed": result }));
+    }
+
+    if json {
+        println!("{}", Value::Array(removed));
+    }
+
+    Ok(())
+}
+
+fn update_index(json: bool) -> Result<()> {
+    let conflicts = lhapdf::update_pdfsets_index()?;
+    let sets = lhapdf::remote_sets()?.len();
+
+    if json {
+        let conflicts: Vec<_> = conflicts
+            .into_iter()
+            .map(|conflict| {
+                json!({
+                    "lhaid": conflict.lhaid,
+                    "set": conflict.setname,
+                    "source": conflict.source,
+                    "ignored_set": conflict.ignored_setname,
+                    "ignored_source": conflict.ignored_source,
+                })
+            })
+            .collect();
+        println!("{}", json!({ "sets": sets, "conflicts": conflicts }));
+    } else {
+        println!("pdfsets.index lists {sets} PDF sets");
+
+        for conflict in conflicts {
+            println!(
+                "LHAID {} is assigned to {} by {}, ignoring {} from {}",
+                conflict.lhaid,
+                conflict.setname,
+                conflict.source,
+                conflict.ignored_setname,
+                conflict.ignored_source
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn config(json: bool) -> Result<()> {
+    let config = Config::get();
+
+    if json {
+        println!("{}", serde_json::to_string(config)?);
+    } else {
+        print!("{}", toml::to_string_pretty(config)?);
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // SAFETY: this is the first thing `main` does, before any other thread is started
+    unsafe { lhapdf::init() };
+
+    let opts = Opts::parse();
+
+    match opts.command {
+        Command::Install { sets } => install(&sets, opts.json),
+        Command::List { remote, .. } => list(remote, opts.json),
+        Command::Show { set } => show(&set, opts.json),
+        Command::Lookup { lhaid } => lookup(lhaid, opts.json),
+        Command::Remove { sets } => remove(&sets, opts.json),
+        Command::UpdateIndex => update_index(opts.json),
+        Command::Config => config(opts.json),
+        Command::Eval {
+            pdf,
+            pid,
+            x,
+            q,
+            opts: eval_opts,
+        } => eval::eval(&pdf, &pid, &x, &q, &eval_opts, opts.json),
+        Command::Alphas {
+            pdf,
+            q,
+            opts: eval_opts,
+        } => eval::alphas(&pdf, &q, &eval_opts, opts.json),
+    }
+}
@@ -0,0 +1,197 @@
+//! Process-wide cache of PDF members with a memory budget.
+
+use super::{Pdf, Result, manager};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Memory budget of the cache in bytes that is used unless [`set_budget`] is called.
+pub const DEFAULT_BUDGET: u64 = 1 << 30;
+
+struct Entry<T> {
+    value: Arc<T>,
+    size: u64,
+    last_use: u64,
+}
+
+/// Least-recently-used cache, which evicts entries when the sum of their sizes exceeds the budget.
+struct Lru<T> {
+    budget: u64,
+    size: u64,
+    clock: u64,
+    entries: BTreeMap<(String, i32), Entry<T>>,
+}
+
+impl<T> Lru<T> {
+    const fn new(budget: u64) -> Self {
+        Self {
+            budget,
+            size: 0,
+            clock: 0,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, setname: &str, member: i32) -> Option<Arc<T>> {
+        self.clock += 1;
+
+        let entry = self.entries.get_mut(&(setname.to_owned(), member))?;
+        entry.last_use = self.clock;
+
+        Some(Arc::clone(&entry.value))
+    }
+
+    /// Insert `value` unless another thread has done so in the meantime, and return the cached
+    /// value.
+    fn insert(&mut self, setname: &str, member: i32, value: T, size: u64) -> Arc<T> {
+        if let Some(value) = self.get(setname, member) {
+            return value;
+        }
+
+        let value = Arc::new(value);
+
+        self.entries.insert(
+            (setname.to_owned(), member),
+            Entry {
+                value: Arc::clone(&value),
+                size,
+                last_use: self.clock,
+            },
+        );
+        self.size += size;
+        self.evict();
+
+        value
+    }
+
+    /// Remove the least-recently-used entries until the cache fits into its budget, but keep the
+    /// entry that was used last.
+    fn evict(&mut self) {
+        while self.size > self.budget && self.entries.len() > 1 {
+            // UNWRAP: the cache has at least two entries
+            let key = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_use)
+                .map(|(key, _)| key.clone())
+                .unwrap();
+            // UNWRAP: the key was just taken from the map
+            let entry = self.entries.remove(&key).unwrap();
+            self.size -= entry.size;
+        }
+    }
+
+    fn set_budget(&mut self, budget: u64) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    fn remove_set(&mut self, setname: &str) {
+        self.entries.retain(|(name, _), entry| {
+            let keep = name != setname;
+
+            if !keep {
+                self.size -= entry.size;
+            }
+
+            keep
+        });
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.size = 0;
+    }
+}
+
+static CACHE: Mutex<Lru<Pdf>> = Mutex::new(Lru::new(DEFAULT_BUDGET));
+
+fn cache() -> std::sync::MutexGuard<'static, Lru<Pdf>> {
+    // the cache is consistent after every method call, even if the thread panicked afterwards
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Return the member `member` of the PDF set `setname`, which is loaded only if it isn't cached.
+pub fn pdf(setname: &str, member: i32) -> Result<Arc<Pdf>> {
+    let cached = cache().get(setname, member);
+
+    if let Some(pdf) = cached {
+        return Ok(pdf);
+    }
+
+    // load the member without holding the lock, so that other threads can use the cache
+    let pdf = Pdf::with_setname_and_member(setname, member)?;
+    // the memory a member needs is approximated by the size of its data file
+    let size = manager::find_file(&format!("{setname}/{setname}_{member:04}.dat"))
+        .and_then(|path| fs::metadata(path).ok())
+        .map_or(0, |metadata| metadata.len());
+
+    Ok(cache().insert(setname, member, pdf, size))
+}
+
+/// Set the memory budget of the cache in bytes, evicting members if needed.
+pub fn set_budget(budget: u64) {
+    cache().set_budget(budget);
+}
+
+/// Return the memory budget of the cache in bytes.
+pub fn budget() -> u64 {
+    cache().budget
+}
+
+/// Return the approximate memory in bytes used by the cached members.
+pub fn size() -> u64 {
+    cache().size
+}
+
+/// Remove all members of the PDF set `setname` from the cache.
+pub fn remove_set(setname: &str) {
+    cache().remove_set(setname);
+}
+
+/// Remove all members from the cache.
+pub fn clear() {
+    cache().clear();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_eviction() {
+        let mut lru = Lru::new(10);
+
+        assert_eq!(*lru.insert("set", 0, 'a', 4), 'a');
+        assert_eq!(*lru.insert("set", 1, 'b', 4), 'b');
+        // an existing entry isn't replaced
+        assert_eq!(*lru.insert("set", 1, 'c', 4), 'b');
+        assert_eq!(lru.get("set", 0).as_deref(), Some(&'a'));
+        assert_eq!(lru.size, 8);
+
+        // `set/1` was used least recently
+        lru.insert("set", 2, 'd', 4);
+        assert_eq!(lru.get("set", 1), None);
+        assert_eq!(lru.get("set", 0).as_deref(), Some(&'a'));
+        assert_eq!(lru.size, 8);
+
+        // the last entry is kept even if it alone exceeds the budget
+        lru.insert("other", 0, 'e', 20);
+        assert_eq!(lru.entries.len(), 1);
+        assert_eq!(lru.get("other", 0).as_deref(), Some(&'e'));
+
+        lru.set_budget(0);
+        assert_eq!(lru.size, 20);
+
+        lru.set_budget(30);
+        lru.insert("set", 3, 'f', 4);
+        lru.remove_set("other");
+        assert_eq!(lru.get("other", 0), None);
+        assert_eq!(lru.get("set", 3).as_deref(), Some(&'f'));
+        assert_eq!(lru.size, 4);
+
+        lru.clear();
+        assert_eq!(lru.size, 0);
+        assert_eq!(lru.get("other", 0), None);
+    }
+}
@@ -0,0 +1,841 @@
+//! Conversions between sets of Monte Carlo replicas and sets of Hessian eigenvectors, and the
+//! combination and compression of replica sets.
+//!
+//! The functions evaluate the members of a set, for instance the ones returned by
+//! `PdfSet::mk_pdfs`, on the knots of the new set and return a [`PdfSetWriter`] whose `ErrorType`
+//! is set accordingly, so that the new set can be written or registered. The strong coupling isn't
+//! converted; it can be tabulated with [`PdfSetWriter::set_alphas_ipol`].
+
+use super::backend::PdfBackend;
+use super::writer::PdfSetWriter;
+use super::{Error, Result};
+
+/// Convert the Monte Carlo replicas in `replicas` into a symmetric Hessian set with
+/// `num_eigenvectors` eigenvectors, using the method of [arXiv:1505.06736].
+///
+/// The first member of `replicas` is the central member and ignored. The covariance of the
+/// replicas is evaluated on the knots of the subgrids in `knots` and the eigenvectors with the
+/// largest eigenvalues are kept, each one being a linear combination of the replicas. The central
+/// member of the new set is the average of the replicas.
+///
+/// [arXiv:1505.06736]: https://arxiv.org/abs/1505.06736
+///
+/// # Errors
+///
+/// If `num_eigenvectors` isn't smaller than the number of replicas, the replicas don't have the
+/// same flavours, or `name` or `knots` are invalid an error is returned.
+pub fn mc2hessian<P: PdfBackend>(
+    name: &str,
+    replicas: &[P],
+    knots: &[(Vec<f64>, Vec<f64>)],
+    num_eigenvectors: usize,
+) -> Result<PdfSetWriter> {
+    let replicas: Vec<_> = replicas.iter().skip(1).collect();
+
+    if num_eigenvectors == 0 || num_eigenvectors >= replicas.len() {
+        return Err(Error::General(format!(
+            "can not construct {num_eigenvectors} eigenvectors from {} replicas",
+            replicas.len()
+        )));
+    }
+
+    let mut writer = new_writer(name, &replicas, knots)?;
+    let tables = tabulate(&writer, &replicas);
+    let central = mean(&tables);
+    let deviations: Vec<_> = tables
+        .iter()
+        .map(|table| linear_combination(table, &[&central], &[-1.0]))
+        .collect();
+    let deviations: Vec<_> = deviations.iter().collect();
+    let norm = count(replicas.len() - 1);
+
+    let mut covariance = vec![vec![0.0; replicas.len()]; replicas.len()];
+
+    for i in 0..replicas.len() {
+        for j in 0..=i {
+            let value = deviations[i]
+                .iter()
+                .flatten()
+                .zip(deviations[j].iter().flatten())
+                .map(|(lhs, rhs)| lhs * rhs)
+                .sum::<f64>()
+                / norm;
+            covariance[i][j] = value;
+            covariance[j][i] = value;
+        }
+    }
+
+    writer.set_error_type("symmhessian");
+    writer.add_member(central.clone())?;
+
+    for eigenvector in eigenvectors(covariance).into_iter().take(num_eigenvectors) {
+        let coefficients: Vec<_> = eigenvector.iter().map(|v| v / norm.sqrt()).collect();
+        writer.add_member(linear_combination(&central, &deviations, &coefficients))?;
+    }
+
+    Ok(writer)
+}
+
+/// Convert the Hessian set with the members in `members` into a set with `num_replicas` Monte
+/// Carlo replicas, using the method of [arXiv:1205.4024].
+///
+/// The error type `error_type` of the Hessian set must be either `symmhessian` or `hessian`. The
+/// replicas are evaluated on the knots of the subgrids in `knots`, and the random numbers are
+/// generated from `seed`, so that the same seed always gives the same replicas. The eigenvectors
+/// must correspond to one standard deviation; sets given at a different confidence level, for
+/// instance 90%, have to be rescaled first. The central member of the new set is the average of
+/// the replicas.
+///
+/// [arXiv:1205.4024]: https://arxiv.org/abs/1205.4024
+///
+/// # Errors
+///
+/// If `error_type` isn't a Hessian error type, the number of members doesn't match it, the
+/// members don't have the same flavours, or `name` or `knots` are invalid an error is returned.
+pub fn hessian2replicas<P: PdfBackend>(
+    name: &str,
+    members: &[P],
+    error_type: &str,
+    knots: &[(Vec<f64>, Vec<f64>)],
+    num_replicas: usize,
+    seed: u64,
+) -> Result<PdfSetWriter> {
+    let symmetric = match error_type {
+        "symmhessian" => true,
+        "hessian" => false,
+        _ => {
+            return Err(Error::General(format!(
+                "'{error_type}' is not a Hessian error type"
+            )));
+        }
+    };
+
+    if members.len() < 2 || (!symmetric && members.len().is_multiple_of(2)) {
+        return Err(Error::General(format!(
+            "{} members don't form a set with error type '{error_type}'",
+            members.len()
+        )));
+    }
+
+    if num_replicas == 0 {
+        return Err(Error::General(
+            "at least one replica must be generated".to_owned(),
+        ));
+    }
+
+    let members: Vec<_> = members.iter().collect();
+    let writer = new_writer(name, &members, knots)?;
+    let tables = tabulate(&writer, &members);
+    let central = &tables[0];
+    let deviations: Vec<_> = tables[1..]
+        .iter()
+        .map(|table| linear_combination(table, &[central], &[-1.0]))
+        .collect();
+    let deviations: Vec<_> = deviations.iter().collect();
+    let mut rng = Rng::new(seed);
+    let mut replicas = Vec::with_capacity(num_replicas);
+
+    for _ in 0..num_replicas {
+        let mut coefficients = vec![0.0; deviations.len()];
+
+        if symmetric {
+            coefficients.fill_with(|| rng.normal());
+        } else {
+            // the members come in pairs of upward and downward variations, and the variation is
+            // chosen according to the sign of the random number
+            for pair in coefficients.chunks_mut(2) {
+                let random = rng.normal();
+
+                if random > 0.0 {
+                    pair[0] = random;
+                } else {
+                    pair[1] = -random;
+                }
+            }
+        }
+
+        replicas.push(linear_combination(central, &deviations, &coefficients));
+    }
+
+    add_replicas(writer, replicas)
+}
+
+/// Combine the replicas of the sets in `sets` into a new set with `num_replicas` replicas, in
+/// which every set contributes a number of replicas proportional to its weight in `weights`.
+///
+/// The first member of every set is the central member and ignored; of the remaining ones the
+/// first replicas are taken, in the order of `sets`. The members are evaluated on the knots of the
+/// subgrids in `knots`. With equal weights this is the Monte Carlo combination used for PDF4LHC15,
+/// see [arXiv:1510.03865].
+///
+/// [arXiv:1510.03865]: https://arxiv.org/abs/1510.03865
+///
+/// # Errors
+///
+/// If the number of weights doesn't match the number of sets, the weights are negative or all
+/// zero, a set doesn't have enough replicas, the sets don't have the same flavours, or `name` or
+/// `knots` are invalid an error is returned.
+pub fn combine<P: PdfBackend>(
+    name: &str,
+    sets: &[&[P]],
+    weights: &[f64],
+    knots: &[(Vec<f64>, Vec<f64>)],
+    num_replicas: usize,
+) -> Result<PdfSetWriter> {
+    if sets.len() != weights.len() {
+        return Err(Error::General(format!(
+            "{} weights given for {} sets",
+            weights.len(),
+            sets.len()
+        )));
+    }
+
+    let total: f64 = weights.iter().sum();
+
+    if weights
+        .iter()
+        .any(|weight| !weight.is_finite() || *weight < 0.0)
+        || total <= 0.0
+    {
+        return Err(Error::General(
+            "weights must be finite, not negative and not all zero".to_owned(),
+        ));
+    }
+
+    // distribute the replicas with the largest remainder method, so that they add up exactly
+    let exact: Vec<_> = weights
+        .iter()
+        .map(|weight| weight / total * count(num_replicas))
+        .collect();
+    let mut counts: Vec<_> = exact.iter().map(|&exact| floor(exact)).collect();
+    let mut order: Vec<_> = (0..sets.len()).collect();
+    order.sort_by(|&i, &j| (exact[j] - exact[j].floor()).total_cmp(&(exact[i] - exact[i].floor())));
+
+    for &index in order
+        .iter()
+        .cycle()
+        .take(num_replicas - counts.iter().sum::<usize>())
+    {
+        counts[index] += 1;
+    }
+
+    let mut members = Vec::with_capacity(num_replicas);
+
+    for (index, (set, &count)) in sets.iter().zip(&counts).enumerate() {
+        let replicas = set.get(1..).unwrap_or(&[]);
+
+        if count > replicas.len() {
+            return Err(Error::General(format!(
+                "set {index} has {} replicas, but {count} are needed",
+                replicas.len()
+            )));
+        }
+
+        members.extend(&replicas[..count]);
+    }
+
+    let writer = new_writer(name, &members, knots)?;
+    let tables = tabulate(&writer, &members);
+
+    add_replicas(writer, tables)
+}
+
+/// Compress the Monte Carlo replicas in `replicas` into a set with `num_replicas` of them.
+///
+/// The selected replicas reproduce the mean, the standard deviation and the correlations of the
+/// original replicas as well as possible, similarly to [arXiv:1504.06469]. The first member of
+/// `replicas` is the central member and ignored. The statistics are evaluated on the knots of the
+/// subgrids in `knots`, where the replicas are also tabulated. Starting from a random selection,
+/// in each of the `iterations` steps a selected replica is randomly exchanged with one that isn't
+/// selected, which is kept if it improves the agreement. The random numbers are generated from
+/// `seed`, so that the same seed always gives the same replicas.
+///
+/// [arXiv:1504.06469]: https://arxiv.org/abs/1504.06469
+///
+/// # Errors
+///
+/// If `num_replicas` isn't smaller than the number of replicas, the replicas don't have the same
+/// flavours, or `name` or `knots` are invalid an error is returned.
+pub fn compress<P: PdfBackend>(
+    name: &str,
+    replicas: &[P],
+    knots: &[(Vec<f64>, Vec<f64>)],
+    num_replicas: usize,
+    iterations: usize,
+    seed: u64,
+) -> Result<PdfSetWriter> {
+    let replicas: Vec<_> = replicas.iter().skip(1).collect();
+
+    if num_replicas < 2 || num_replicas >= replicas.len() {
+        return Err(Error::General(format!(
+            "can not compress {} replicas into {num_replicas}",
+            replicas.len()
+        )));
+    }
+
+    let writer = new_writer(name, &replicas, knots)?;
+    let tables = tabulate(&writer, &replicas);
+    let values: Vec<_> = tables.iter().map(|table| table.concat()).collect();
+    let mut rng = Rng::new(seed);
+
+    // the first `num_replicas` indices are the selected replicas
+    let mut order: Vec<_> = (0..replicas.len()).collect();
+
+    for i in (1..order.len()).rev() {
+        order.swap(i, rng.below(i + 1));
+    }
+
+    let prior = Moments::new(&values, &order);
+    let mut moments = Moments::new(&values, &order[..num_replicas]);
+    let mut error = moments.error(&prior);
+
+    for _ in 0..iterations {
+        let i = rng.below(num_replicas);
+        let j = num_replicas + rng.below(order.len() - num_replicas);
+
+        moments.exchange(&values[order[i]], &values[order[j]]);
+        let new_error = moments.error(&prior);
+
+        if new_error < error {
+            error = new_error;
+            order.swap(i, j);
+        } else {
+            moments.exchange(&values[order[j]], &values[order[i]]);
+        }
+    }
+
+    let mut selected = order[..num_replicas].to_vec();
+    selected.sort_unstable();
+
+    add_replicas(
+        writer,
+        selected.into_iter().map(|i| tables[i].clone()).collect(),
+    )
+}
+
+/// Sums of the values, their squares and the products of pairs of values of a number of
+/// replicas, from which their statistics are calculated.
+struct Moments {
+    /// Indices of the values whose statistics are compared.
+    points: Vec<usize>,
+    /// Pairs of indices into `points` whose correlations are compared.
+    pairs: Vec<(usize, usize)>,
+    count: f64,
+    sum: Vec<f64>,
+    sum2: Vec<f64>,
+    products: Vec<f64>,
+}
+
+impl Moments {
+    /// Maximum number of points whose correlations are compared.
+    const CORRELATED_POINTS: usize = 50;
+
+    fn new(values: &[Vec<f64>], replicas: &[usize]) -> Self {
+        let len = values[0].len();
+        // points where all replicas agree, for instance at `x = 1`, carry no information
+        let points: Vec<_> = (0..len)
+            .filter(|&point| {
+                values.iter().any(|replica| {
+                    (replica[point] - values[0][point]).abs()
+                        > f64::EPSILON * values[0][point].abs()
+                })
+            })
+            .collect();
+        let step = (points.len() / Self::CORRELATED_POINTS).max(1);
+        let correlated: Vec<_> = (0..points.len()).step_by(step).collect();
+        let pairs = correlated
+            .iter()
+            .enumerate()
+            .flat_map(|(n, &i)| correlated[n + 1..].iter().map(move |&j| (i, j)))
+            .collect();
+
+        let mut result = Self {
+            count: 0.0,
+            sum: vec![0.0; points.len()],
+            sum2: vec![0.0; points.len()],
+            products: vec![0.0; correlated.len() * (correlated.len().saturating_sub(1)) / 2],
+            points,
+            pairs,
+        };
+
+        for &replica in replicas {
+            result.add(&values[replica], 1.0);
+        }
+
+        result
+    }
+
+    fn add(&mut self, values: &[f64], sign: f64) {
+        self.count += sign;
+
+        for (n, &point) in self.points.iter().enumerate() {
+            self.sum[n] += sign * values[point];
+            self.sum2[n] += sign * values[point] * values[point];
+        }
+
+        for (product, &(i, j)) in self.products.iter_mut().zip(&self.pairs) {
+            *product += sign * values[self.points[i]] * values[self.points[j]];
+        }
+    }
+
+    /// Replace the replica with the values `old` by the one with the values `new`.
+    fn exchange(&mut self, old: &[f64], new: &[f64]) {
+        self.add(old, -1.0);
+        self.add(new, 1.0);
+    }
+
+    fn mean(&self, n: usize) -> f64 {
+        self.sum[n] / self.count
+    }
+
+    fn covariance(&self, product: f64, i: usize, j: usize) -> f64 {
+        (product - self.sum[i] * self.sum[j] / self.count) / (self.count - 1.0)
+    }
+
+    fn std_dev(&self, n: usize) -> f64 {
+        self.covariance(self.sum2[n], n, n).max(0.0).sqrt()
+    }
+
+    fn correlation(&self, pair: usize) -> f64 {
+        let (i, j) = self.pairs[pair];
+        self.covariance(self.products[pair], i, j) / (self.std_dev(i) * self.std_dev(j))
+    }
+
+    /// Sum of the mean squared relative differences of the means, standard deviations and
+    /// correlations of these replicas compared to the ones of `prior`.
+    fn error(&self, prior: &Self) -> f64 {
+        let points = count(self.points.len());
+        let mean = (0..self.points.len())
+            .map(|n| ((self.mean(n) - prior.mean(n)) / prior.std_dev(n)).powi(2))
+            .sum::<f64>()
+            / points;
+        let std_dev = (0..self.points.len())
+            .map(|n| (self.std_dev(n) / prior.std_dev(n) - 1.0).powi(2))
+            .sum::<f64>()
+            / points;
+        let correlation = (0..self.pairs.len())
+            .map(|pair| (self.correlation(pair) - prior.correlation(pair)).powi(2))
+            .sum::<f64>()
+            / count(self.pairs.len().max(1));
+
+        mean + std_dev + correlation
+    }
+}
+
+fn add_replicas(mut writer: PdfSetWriter, replicas: Vec<Vec<Vec<f64>>>) -> Result<PdfSetWriter> {
+    writer.set_error_type("replicas");
+    writer.add_member(mean(&replicas))?;
+
+    for replica in replicas {
+        writer.add_member(replica)?;
+    }
+
+    Ok(writer)
+}
+
+fn new_writer<P: PdfBackend>(
+    name: &str,
+    members: &[&P],
+    knots: &[(Vec<f64>, Vec<f64>)],
+) -> Result<PdfSetWriter> {
+    let flavors = members.first().map(|pdf| pdf.flavors()).unwrap_or_default();
+
+    if members.iter().any(|pdf| pdf.flavors() != flavors) {
+        return Err(Error::General(
+            "the members don't have the same flavours".to_owned(),
+        ));
+    }
+
+    let mut writer = PdfSetWriter::new(name, flavors)?;
+
+    for (x, q2) in knots {
+        writer.add_subgrid(x.clone(), q2.clone())?;
+    }
+
+    Ok(writer)
+}
+
+fn tabulate<P: PdfBackend>(writer: &PdfSetWriter, members: &[&P]) -> Vec<Vec<Vec<f64>>> {
+    members
+        .iter()
+        .map(|pdf| writer.tabulate(|pid, x, q2| pdf.xfx_q2(pid, x, q2)))
+        .collect()
+}
+
+// ALLOW: the number of members is small enough to be represented exactly
+#[allow(clippy::cast_precision_loss)]
+const fn count(n: usize) -> f64 {
+    n as f64
+}
+
+// ALLOW: `value` is non-negative and small
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn floor(value: f64) -> usize {
+    value as usize
+}
+
+/// Average of the tables in `tables`, which must not be empty.
+fn mean(tables: &[Vec<Vec<f64>>]) -> Vec<Vec<f64>> {
+    let refs: Vec<_> = tables.iter().collect();
+    let coefficients = vec![1.0 / count(tables.len()); tables.len()];
+
+    linear_combination(&[], &refs, &coefficients)
+}
+
+/// Return `base + sum_i coefficients[i] * tables[i]`. If `base` is empty, it is taken to be zero.
+fn linear_combination(
+    base: &[Vec<f64>],
+    tables: &[&Vec<Vec<f64>>],
+    coefficients: &[f64],
+) -> Vec<Vec<f64>> {
+    let mut result = if base.is_empty() {
+        tables[0]
+            .iter()
+            .map(|subgrid| vec![0.0; subgrid.len()])
+            .collect()
+    } else {
+        base.to_vec()
+    };
+
+    for (table, &coefficient) in tables.iter().zip(coefficients) {
+        for (result, subgrid) in result.iter_mut().zip(table.iter()) {
+            for (result, value) in result.iter_mut().zip(subgrid) {
+                *result += coefficient * value;
+            }
+        }
+    }
+
+    result
+}
+
+/// Return the normalized eigenvectors of the symmetric `matrix`, ordered by decreasing
+/// eigenvalue, using the cyclic Jacobi method.
+fn eigenvectors(mut matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut vectors: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    // the method converges quadratically, so this is never reached in practice
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .map(|(i, j)| matrix[i][j].powi(2))
+            .sum();
+        let diagonal: f64 = (0..n).map(|i| matrix[i][i].powi(2)).sum();
+
+        if off_diagonal <= f64::EPSILON.powi(2) * diagonal {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if matrix[p][q].abs() < f64::MIN_POSITIVE {
+                    continue;
+                }
+
+                let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                let c = 1.0 / t.hypot(1.0);
+                let s = t * c;
+
+                for row in &mut matrix {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+
+                let (head, tail) = matrix.split_at_mut(q);
+
+                for (pk, qk) in head[p].iter_mut().zip(&mut tail[0]) {
+                    (*pk, *qk) = (c * *pk - s * *qk, s * *pk + c * *qk);
+                }
+
+                for row in &mut vectors {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<_> = (0..n).collect();
+    order.sort_by(|&i, &j| matrix[j][j].total_cmp(&matrix[i][i]));
+
+    order
+        .into_iter()
+        .map(|k| vectors.iter().map(|row| row[k]).collect())
+        .collect()
+}
+
+/// Small random number generator based on `SplitMix64`, which is good enough to sample replicas
+/// and makes them reproducible without further dependencies.
+struct Rng(u64);
+
+impl Rng {
+    const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    const fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed number in the open interval `(0, 1)`.
+    // ALLOW: the shifted value has 53 bits, which `f64` represents exactly
+    #[allow(clippy::cast_precision_loss)]
+    fn uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / 9_007_199_254_740_992.0
+    }
+
+    /// Uniformly distributed integer in the range `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        floor(self.uniform() * count(n)).min(n - 1)
+    }
+
+    /// Normally distributed number with mean zero and unit variance.
+    fn normal(&mut self) -> f64 {
+        let (u1, u2) = (self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// PDF whose members differ from the central one along two directions.
+    struct Toy(f64, f64);
+
+    impl PdfBackend for Toy {
+        fn xfx_q2(&self, id: i32, x: f64, q2: f64) -> f64 {
+            let central = f64::from(id) * x.sqrt() * (1.0 - x).powi(3);
+            central * self.1.mul_add((1.0 - x) * q2.ln(), self.0.mul_add(x, 1.0))
+        }
+
+        fn alphas_q2(&self, _: f64) -> f64 {
+            0.118
+        }
+
+        fn flavors(&self) -> Vec<i32> {
+            vec![1, 21]
+        }
+
+        fn x_min(&self) -> f64 {
+            1e-3
+        }
+
+        fn x_max(&self) -> f64 {
+            1.0
+        }
+
+        fn q2_min(&self) -> f64 {
+            2.0
+        }
+
+        fn q2_max(&self) -> f64 {
+            1e3
+        }
+    }
+
+    fn knots() -> Vec<(Vec<f64>, Vec<f64>)> {
+        vec![(vec![1e-3, 1e-2, 0.1, 0.5, 1.0], vec![2.0, 10.0, 100.0, 1e3])]
+    }
+
+    fn values(writer: &PdfSetWriter, member: usize) -> &[f64] {
+        writer.members()[member].subgrids()[0].values()
+    }
+
+    #[test]
+    fn eigenvectors_of_symmetric_matrix() {
+        let vectors = eigenvectors(vec![
+            vec![2.0, 1.0, 0.0],
+            vec![1.0, 2.0, 0.0],
+            vec![0.0, 0.0, 5.0],
+        ]);
+
+        // eigenvalues are 5, 3 and 1
+        assert!((vectors[0][2].abs() - 1.0).abs() < 1e-14);
+        assert!((vectors[1][0] - vectors[1][1]).abs() < 1e-14);
+        assert!((vectors[2][0] + vectors[2][1]).abs() < 1e-14);
+        assert!((vectors[1][0].abs() - 0.5_f64.sqrt()).abs() < 1e-14);
+    }
+
+    #[test]
+    fn replicas_to_hessian() -> Result<()> {
+        let mut rng = Rng::new(42);
+        let replicas: Vec<_> = (0..21)
+            .map(|_| Toy(0.1 * rng.normal(), 0.05 * rng.normal()))
+            .collect();
+        let writer = mc2hessian("ToyHessian", &replicas, &knots(), 2)?;
+
+        assert_eq!(writer.info().get("ErrorType"), Some("symmhessian"));
+        assert_eq!(writer.num_members(), 3);
+
+        // the replicas only vary in two directions, so two eigenvectors describe them exactly
+        let tables = tabulate(&writer, &replicas[1..].iter().collect::<Vec<_>>());
+        let central = mean(&tables);
+
+        for (index, &central) in central[0].iter().enumerate() {
+            let variance = tables
+                .iter()
+                .map(|table| (table[0][index] - central).powi(2))
+                .sum::<f64>()
+                / 19.0;
+            let hessian = (1..=2)
+                .map(|member| (values(&writer, member)[index] - central).powi(2))
+                .sum::<f64>();
+
+            assert!((values(&writer, 0)[index] - central).abs() < 1e-14);
+            assert!((hessian - variance).abs() <= 1e-12 * variance);
+        }
+
+        assert_eq!(
+            mc2hessian("ToyHessian", &replicas, &knots(), 20)
+                .unwrap_err()
+                .to_string(),
+            "can not construct 20 eigenvectors from 20 replicas"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn hessian_to_replicas() -> Result<()> {
+        let members = [Toy(0.0, 0.0), Toy(0.2, 0.0), Toy(0.0, 0.1)];
+        let writer = hessian2replicas("ToyReplicas", &members, "symmhessian", &knots(), 4000, 1)?;
+
+        assert_eq!(writer.info().get("ErrorType"), Some("replicas"));
+        assert_eq!(writer.num_members(), 4001);
+
+        // the value at x = 0.1 and Q^2 = 100 of the gluon
+        let index = (2 * 4 + 2) * 2 + 1;
+        let central = members[0].xfx_q2(21, 0.1, 100.0);
+        let hessian = members[1..]
+            .iter()
+            .map(|pdf| (pdf.xfx_q2(21, 0.1, 100.0) - central).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        let replicas: Vec<_> = (1..=4000)
+            .map(|member| values(&writer, member)[index])
+            .collect();
+        let std_dev = (replicas
+            .iter()
+            .map(|value| (value - central).powi(2))
+            .sum::<f64>()
+            / 3999.0)
+            .sqrt();
+
+        assert!((values(&writer, 0)[index] - central).abs() < 0.05 * hessian);
+        assert!((std_dev / hessian - 1.0).abs() < 0.05);
+
+        // upward variations only occur with positive random numbers
+        let members = [Toy(0.0, 0.0), Toy(0.2, 0.0), Toy(-0.1, 0.0)];
+        let writer = hessian2replicas("ToyReplicas", &members, "hessian", &knots(), 4000, 1)?;
+        let mean = values(&writer, 0)[index];
+        let upward = members[1].xfx_q2(21, 0.1, 100.0) - central;
+        let downward = members[2].xfx_q2(21, 0.1, 100.0) - central;
+
+        // the average of |r| for normally distributed r is sqrt(2 / pi)
+        let expected = central + (upward + downward) / (2.0 * std::f64::consts::PI).sqrt();
+
+        assert!((mean - expected).abs() < 0.05 * upward);
+
+        assert_eq!(
+            hessian2replicas("ToyReplicas", &members[..2], "hessian", &knots(), 10, 1)
+                .unwrap_err()
+                .to_string(),
+            "2 members don't form a set with error type 'hessian'"
+        );
+        assert_eq!(
+            hessian2replicas("ToyReplicas", &members, "replicas", &knots(), 10, 1)
+                .unwrap_err()
+                .to_string(),
+            "'replicas' is not a Hessian error type"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn combine_sets() -> Result<()> {
+        let first: Vec<_> = (0..5).map(|i| Toy(0.1 * f64::from(i), 0.0)).collect();
+        let second: Vec<_> = (0..10).map(|i| Toy(0.0, 0.1 * f64::from(i))).collect();
+        let writer = combine("ToyCombined", &[&first, &second], &[1.0, 3.0], &knots(), 8)?;
+
+        assert_eq!(writer.info().get("ErrorType"), Some("replicas"));
+        assert_eq!(writer.num_members(), 9);
+
+        // two replicas are taken from the first set and six from the second one
+        let expected: Vec<_> = first[1..3].iter().chain(&second[1..7]).collect();
+
+        for (member, pdf) in expected.iter().enumerate() {
+            assert_eq!(
+                values(&writer, member + 1),
+                writer.tabulate(|pid, x, q2| pdf.xfx_q2(pid, x, q2))[0]
+            );
+        }
+
+        assert_eq!(
+            combine("ToyCombined", &[&first, &second], &[3.0, 1.0], &knots(), 8)
+                .unwrap_err()
+                .to_string(),
+            "set 0 has 4 replicas, but 6 are needed"
+        );
+        assert_eq!(
+            combine("ToyCombined", &[&first, &second], &[1.0], &knots(), 8)
+                .unwrap_err()
+                .to_string(),
+            "1 weights given for 2 sets"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn compress_replicas() -> Result<()> {
+        let mut rng = Rng::new(7);
+        let replicas: Vec<_> = (0..101)
+            .map(|_| Toy(0.1 * rng.normal(), 0.05 * rng.normal()))
+            .collect();
+        let writer = compress("ToyCompressed", &replicas, &knots(), 20, 2000, 3)?;
+
+        assert_eq!(writer.info().get("ErrorType"), Some("replicas"));
+        assert_eq!(writer.num_members(), 21);
+
+        let prior: Vec<_> = tabulate(&writer, &replicas[1..].iter().collect::<Vec<_>>())
+            .into_iter()
+            .map(|table| table.concat())
+            .collect();
+        let compressed: Vec<_> = (1..=20)
+            .map(|member| values(&writer, member).to_vec())
+            .collect();
+        let all: Vec<_> = (0..100).collect();
+        let prior = Moments::new(&prior, &all);
+        let compressed = Moments::new(&compressed, &(0..20).collect::<Vec<_>>());
+
+        // the gluon at x = 0.1 and Q^2 = 100
+        let point = prior
+            .points
+            .iter()
+            .position(|&point| point == (2 * 4 + 2) * 2 + 1)
+            .unwrap();
+
+        assert!((compressed.mean(point) - prior.mean(point)).abs() < 0.1 * prior.std_dev(point));
+        assert!((compressed.std_dev(point) / prior.std_dev(point) - 1.0).abs() < 0.1);
+
+        assert_eq!(
+            compress("ToyCompressed", &replicas, &knots(), 100, 10, 3)
+                .unwrap_err()
+                .to_string(),
+            "can not compress 100 replicas into 100"
+        );
+
+        Ok(())
+    }
+}
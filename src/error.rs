@@ -1,3 +1,4 @@
+#[cfg(feature = "lhapdf")]
 use cxx::Exception;
 use thiserror::Error;
 
@@ -5,6 +6,7 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum Error {
     /// Captures an exception coming from the C++ LHAPDF library.
+    #[cfg(feature = "lhapdf")]
     #[error(transparent)]
     LhapdfException(Exception),
     /// General error with a message.
@@ -21,6 +23,7 @@ pub enum Error {
 /// Type definition for results with an [`enum@Error`].
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(feature = "lhapdf")]
 impl From<Exception> for Error {
     fn from(err: Exception) -> Self {
         Self::LhapdfException(err)
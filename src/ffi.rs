@@ -42,13 +42,24 @@ mod ffi {
         fn alphasQ2(self: &PDF, q2: f64) -> Result<f64>;
         fn xfxQ2(self: &PDF, id: i32, x: f64, q2: f64) -> Result<f64>;
         fn lhapdfID(self: &PDF) -> i32;
-        fn xMin(self: Pin<&mut PDF>) -> f64;
-        fn xMax(self: Pin<&mut PDF>) -> f64;
         fn setFlavors(self: Pin<&mut PDF>, flavors: &CxxVector<i32>);
         fn setForcePositive(self: Pin<&mut PDF>, mode: i32);
         fn flavors<'a>(self: &'a PDF) -> &'a CxxVector<i32>;
         fn forcePositive(self: &PDF) -> i32;
 
+        type AlphaS;
+
+        fn alphasQ2(self: &AlphaS, q2: f64) -> Result<f64>;
+        fn numFlavorsQ2(self: &AlphaS, q2: f64) -> Result<i32>;
+        fn quarkMass(self: &AlphaS, id: i32) -> Result<f64>;
+        fn quarkThreshold(self: &AlphaS, id: i32) -> Result<f64>;
+        fn setOrderQCD(self: Pin<&mut AlphaS>, order: i32);
+        fn setQuarkMass(self: Pin<&mut AlphaS>, id: i32, value: f64) -> Result<()>;
+        fn setQuarkThreshold(self: Pin<&mut AlphaS>, id: i32, value: f64) -> Result<()>;
+        fn setMZ(self: Pin<&mut AlphaS>, mz: f64);
+        fn setAlphaSMZ(self: Pin<&mut AlphaS>, alphas: f64);
+        fn setLambda(self: Pin<&mut AlphaS>, i: u32, lambda: f64);
+
         type PDFSet;
 
         fn has_key(self: &PDFSet, key: &CxxString) -> bool;
@@ -61,6 +72,32 @@ mod ffi {
         include!("managed-lhapdf/include/wrappers.hpp");
 
         fn pdf_setname(pdf: &PDF, setname: Pin<&mut CxxString>);
+        fn pdf_info_entry(pdf: &PDF, key: &CxxString, value: Pin<&mut CxxString>) -> Result<()>;
+        fn pdf_has_local_entry(pdf: &PDF, key: &CxxString) -> bool;
+        fn pdf_alphas(pdf: &PDF) -> &AlphaS;
+        fn pdf_order_qcd(pdf: &PDF) -> Result<i32>;
+        fn pdf_quark_mass(pdf: &PDF, id: i32) -> Result<f64>;
+        fn pdf_quark_threshold(pdf: &PDF, id: i32) -> Result<f64>;
+        fn pdf_x_min(pdf: &PDF) -> f64;
+        fn pdf_x_max(pdf: &PDF) -> f64;
+        fn pdf_q2_min(pdf: &PDF) -> f64;
+        fn pdf_q2_max(pdf: &PDF) -> f64;
+        fn pdf_set_alphas(pdf: Pin<&mut PDF>, alphas: UniquePtr<AlphaS>);
+        fn pdf_interpolator(pdf: &PDF, name: Pin<&mut CxxString>) -> Result<()>;
+        fn pdf_set_interpolator(pdf: Pin<&mut PDF>, name: &CxxString) -> Result<()>;
+        fn pdf_extrapolator(pdf: &PDF, name: Pin<&mut CxxString>) -> Result<()>;
+        fn pdf_set_extrapolator(pdf: Pin<&mut PDF>, name: &CxxString) -> Result<()>;
+        fn alphas_analytic() -> UniquePtr<AlphaS>;
+        fn alphas_ode() -> UniquePtr<AlphaS>;
+        fn alphas_ipol() -> UniquePtr<AlphaS>;
+        fn alphas_type(alphas: &AlphaS, name: Pin<&mut CxxString>);
+        fn alphas_order_qcd(alphas: &AlphaS) -> i32;
+        fn alphas_set_ipol_values(
+            alphas: Pin<&mut AlphaS>,
+            q: &[f64],
+            values: &[f64],
+        ) -> Result<()>;
+
         fn pdf_with_setname_and_member(setname: &CxxString, member: i32) -> Result<UniquePtr<PDF>>;
         fn pdfset_new(setname: &CxxString) -> Result<UniquePtr<PDFSet>>;
         fn pdfset_setname(pdf: &PDFSet, setname: Pin<&mut CxxString>);
@@ -68,6 +105,9 @@ mod ffi {
         #[cfg(feature = "managed")]
         fn empty_lhaindex();
 
+        fn find_file(target: &CxxString, path: Pin<&mut CxxString>);
+        fn search_paths() -> UniquePtr<CxxVector<CxxString>>;
+        fn lhapdf_version(version: Pin<&mut CxxString>);
         fn lookup_pdf_setname(lhaid: i32, setname: Pin<&mut CxxString>);
         fn lookup_pdf_memberid(lhaid: i32) -> i32;
         fn get_pdfset_error_type(set: &PDFSet, setname: Pin<&mut CxxString>);
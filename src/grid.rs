@@ -0,0 +1,766 @@
+//! Pure-Rust reader for PDF sets in the LHAPDF6 format.
+//!
+//! A set consists of an `.info` file with the metadata of the set and one `_NNNN.dat` file per
+//! member in the `lhagrid1` format. These types don't need the LHAPDF library and can be used to
+//! inspect and validate grids on their own.
+
+use super::{Error, Result};
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Metadata of a PDF set or member, as stored in `.info` files and the headers of `.dat` files.
+///
+/// This is the subset of YAML that LHAPDF writes: one `Key: value` pair per line, where values are
+/// scalars or flow sequences like `[1, 2, 3]`, which may span several lines.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Info {
+    // the entries in the order in which they were read or set
+    entries: Vec<(String, String)>,
+}
+
+impl Info {
+    /// Read metadata from the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the file could not be read or isn't valid metadata an error is returned.
+    pub fn read(path: &Path) -> Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| Error::General(format!("{}: {err}", path.display())))
+    }
+
+    /// Return the unparsed value of `key`, or `None` if it doesn't exist.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find_map(|(k, value)| (k == key).then_some(value.as_str()))
+    }
+
+    /// Return the value of `key` parsed as a scalar of type `T`. Quotes around strings are
+    /// removed.
+    ///
+    /// # Errors
+    ///
+    /// If the value can not be parsed as `T` an error is returned.
+    pub fn get_as<T: FromStr>(&self, key: &str) -> Result<Option<T>>
+    where
+        T::Err: Display,
+    {
+        self.get(key)
+            .map(|value| parse_scalar(key, value))
+            .transpose()
+    }
+
+    /// Return the value of `key` parsed as a sequence of values of type `T`.
+    ///
+    /// # Errors
+    ///
+    /// If the value isn't a sequence or one of its elements can not be parsed as `T` an error is
+    /// returned.
+    pub fn get_list<T: FromStr>(&self, key: &str) -> Result<Option<Vec<T>>>
+    where
+        T::Err: Display,
+    {
+        self.get(key)
+            .map(|value| {
+                let inner = value
+                    .strip_prefix('[')
+                    .and_then(|value| value.strip_suffix(']'))
+                    .ok_or_else(|| {
+                        Error::General(format!("value of '{key}' is not a sequence: '{value}'"))
+                    })?;
+
+                inner
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|element| !element.is_empty())
+                    .map(|element| parse_scalar(key, element))
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Set `key` to the scalar `value`, replacing an existing value. Strings are written without
+    /// quotes, so they must be valid YAML scalars.
+    pub fn set(&mut self, key: &str, value: impl Display) {
+        self.set_raw(key, value.to_string());
+    }
+
+    /// Set `key` to the sequence `values`, replacing an existing value.
+    pub fn set_list<T: Display>(&mut self, key: &str, values: &[T]) {
+        let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+        self.set_raw(key, format!("[{}]", values.join(", ")));
+    }
+
+    fn set_raw(&mut self, key: &str, value: String) {
+        if let Some((_, old)) = self.entries.iter_mut().find(|(k, _)| k == key) {
+            *old = value;
+        } else {
+            self.entries.push((key.to_owned(), value));
+        }
+    }
+
+    /// Return an iterator over all keys and their unparsed values.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Return the number of members of the set, given by `NumMembers`.
+    ///
+    /// # Errors
+    ///
+    /// If the entry is missing or malformed an error is returned.
+    pub fn num_members(&self) -> Result<usize> {
+        self.require("NumMembers")
+    }
+
+    /// Return the PDG IDs of the partons, given by `Flavors`.
+    ///
+    /// # Errors
+    ///
+    /// If the entry is missing or malformed an error is returned.
+    pub fn flavors(&self) -> Result<Vec<i32>> {
+        self.get_list("Flavors")?
+            .ok_or_else(|| Error::General("missing entry 'Flavors'".to_owned()))
+    }
+
+    /// Return the version of the data, given by `DataVersion`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// If the entry is malformed an error is returned.
+    pub fn data_version(&self) -> Result<Option<i32>> {
+        self.get_as("DataVersion")
+    }
+
+    /// Return the type of the PDF uncertainties, given by `ErrorType`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// If the entry is malformed an error is returned.
+    pub fn error_type(&self) -> Result<Option<String>> {
+        self.get_as("ErrorType")
+    }
+
+    fn require<T: FromStr>(&self, key: &str) -> Result<T>
+    where
+        T::Err: Display,
+    {
+        self.get_as(key)?
+            .ok_or_else(|| Error::General(format!("missing entry '{key}'")))
+    }
+}
+
+impl FromStr for Info {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut info = Self::default();
+
+        for (index, line) in s.lines().enumerate() {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            // indented lines continue the value of the previous key
+            if line.starts_with([' ', '\t'])
+                && let Some((_, value)) = info.entries.last_mut()
+            {
+                if !value.is_empty() && !value.ends_with('[') {
+                    value.push(' ');
+                }
+                value.push_str(trimmed);
+                continue;
+            }
+
+            let Some((key, value)) = trimmed.split_once(':') else {
+                return Err(Error::General(format!(
+                    "line {}: expected 'Key: value', found '{trimmed}'",
+                    index + 1
+                )));
+            };
+
+            info.set_raw(key.trim(), value.trim().to_owned());
+        }
+
+        Ok(info)
+    }
+}
+
+impl Display for Info {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (key, value) in &self.entries {
+            writeln!(f, "{key}: {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_scalar<T: FromStr>(key: &str, value: &str) -> Result<T>
+where
+    T::Err: Display,
+{
+    let unquoted = ['"', '\'']
+        .iter()
+        .find_map(|&quote| {
+            value
+                .strip_prefix(quote)
+                .and_then(|value| value.strip_suffix(quote))
+        })
+        .unwrap_or(value);
+
+    unquoted
+        .parse()
+        .map_err(|err| Error::General(format!("invalid value '{value}' of '{key}': {err}")))
+}
+
+/// A single subgrid of a member, which covers a range in `Q` with its own knots. The values are
+/// `x * f(x, Q)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubGrid {
+    x: Vec<f64>,
+    q: Vec<f64>,
+    flavors: Vec<i32>,
+    // the index of the value for `x[ix]`, `q[iq]` and `flavors[ifl]` is
+    // `(ix * q.len() + iq) * flavors.len() + ifl`, which is the order of the `lhagrid1` format
+    values: Vec<f64>,
+}
+
+impl SubGrid {
+    /// Constructor. The `values` are ordered with the flavour index running fastest, followed by
+    /// the `q` index and the `x` index.
+    ///
+    /// # Errors
+    ///
+    /// If the number of values doesn't match the numbers of knots and flavours an error is
+    /// returned.
+    pub fn new(x: Vec<f64>, q: Vec<f64>, flavors: Vec<i32>, values: Vec<f64>) -> Result<Self> {
+        let expected = x.len() * q.len() * flavors.len();
+
+        if values.len() != expected {
+            return Err(Error::General(format!(
+                "subgrid with {} x knots, {} Q knots and {} flavours needs {expected} values, found {}",
+                x.len(),
+                q.len(),
+                flavors.len(),
+                values.len()
+            )));
+        }
+
+        Ok(Self {
+            x,
+            q,
+            flavors,
+            values,
+        })
+    }
+
+    /// Return the knots in `x`.
+    #[must_use]
+    pub fn x(&self) -> &[f64] {
+        &self.x
+    }
+
+    /// Return the knots in `Q`.
+    #[must_use]
+    pub fn q(&self) -> &[f64] {
+        &self.q
+    }
+
+    /// Return the PDG IDs of the partons in this subgrid.
+    #[must_use]
+    pub fn flavors(&self) -> &[i32] {
+        &self.flavors
+    }
+
+    /// Return all values, ordered as described in [`SubGrid::new`].
+    #[must_use]
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Return the value at the knots `x[ix]` and `q[iq]` for the flavour `flavors[ifl]`.
+    ///
+    /// # Panics
+    ///
+    /// If one of the indices is out of range this method panics.
+    #[must_use]
+    pub fn value(&self, ix: usize, iq: usize, ifl: usize) -> f64 {
+        assert!(ix < self.x.len() && iq < self.q.len() && ifl < self.flavors.len());
+
+        self.values[(ix * self.q.len() + iq) * self.flavors.len() + ifl]
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.x.len() < 2 || self.q.len() < 2 {
+            return Err(Error::General(
+                "subgrid needs at least two knots in both x and Q".to_owned(),
+            ));
+        }
+
+        if !self.x.windows(2).all(|knots| knots[0] < knots[1]) {
+            return Err(Error::General(
+                "x knots are not strictly increasing".to_owned(),
+            ));
+        }
+
+        if !self.q.windows(2).all(|knots| knots[0] < knots[1]) {
+            return Err(Error::General(
+                "Q knots are not strictly increasing".to_owned(),
+            ));
+        }
+
+        if self.x[0] <= 0.0 || self.x[self.x.len() - 1] > 1.0 {
+            return Err(Error::General("x knots are not within (0, 1]".to_owned()));
+        }
+
+        if self.q[0] <= 0.0 {
+            return Err(Error::General("Q knots are not positive".to_owned()));
+        }
+
+        if let Some(value) = self.values.iter().find(|value| !value.is_finite()) {
+            return Err(Error::General(format!(
+                "subgrid contains the value {value}"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A single member of a PDF set, read from a `.dat` file in the `lhagrid1` format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    info: Info,
+    subgrids: Vec<SubGrid>,
+}
+
+impl Member {
+    /// Constructor.
+    #[must_use]
+    pub const fn new(info: Info, subgrids: Vec<SubGrid>) -> Self {
+        Self { info, subgrids }
+    }
+
+    /// Read a member from the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the file could not be read or isn't in the `lhagrid1` format an error is returned.
+    pub fn read(path: &Path) -> Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| Error::General(format!("{}: {err}", path.display())))
+    }
+
+    /// Return the metadata in the header of the member, which usually contains `PdfType` and
+    /// `Format`.
+    #[must_use]
+    pub const fn info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Return the subgrids, ordered in `Q`.
+    #[must_use]
+    pub fn subgrids(&self) -> &[SubGrid] {
+        &self.subgrids
+    }
+
+    /// Check that the knots of every subgrid are strictly increasing and within their allowed
+    /// ranges, that all values are finite, that all subgrids have the same flavours and knots in
+    /// `x`, and that neighbouring subgrids join at the same `Q`.
+    ///
+    /// # Errors
+    ///
+    /// The first problem that is found is returned as an error.
+    pub fn validate(&self) -> Result<()> {
+        if self.subgrids.is_empty() {
+            return Err(Error::General("member has no subgrids".to_owned()));
+        }
+
+        for (index, subgrid) in self.subgrids.iter().enumerate() {
+            subgrid
+                .validate()
+                .map_err(|err| Error::General(format!("subgrid {index}: {err}")))?;
+        }
+
+        for (index, pair) in self.subgrids.windows(2).enumerate() {
+            if pair[0].flavors != pair[1].flavors {
+                return Err(Error::General(format!(
+                    "subgrids {index} and {} have different flavours",
+                    index + 1
+                )));
+            }
+
+            // LHAPDF stores a single array of `x` knots for all subgrids
+            if pair[0].x.len() != pair[1].x.len()
+                || pair[0]
+                    .x
+                    .iter()
+                    .zip(&pair[1].x)
+                    .any(|(lhs, rhs)| lhs.total_cmp(rhs).is_ne())
+            {
+                return Err(Error::General(format!(
+                    "subgrids {index} and {} have different x knots",
+                    index + 1
+                )));
+            }
+
+            // validated subgrids have at least two knots, and LHAPDF requires the knots to be
+            // identical
+            if pair[0].q[pair[0].q.len() - 1]
+                .total_cmp(&pair[1].q[0])
+                .is_ne()
+            {
+                return Err(Error::General(format!(
+                    "subgrids {index} and {} don't join at the same Q",
+                    index + 1
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Member {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.lines().enumerate();
+        let mut header = String::new();
+
+        // the header ends at the first separator
+        for (_, line) in lines.by_ref() {
+            if line.trim() == "---" {
+                break;
+            }
+
+            header.push_str(line);
+            header.push('\n');
+        }
+
+        let info: Info = header.parse()?;
+
+        if let Some(format) = info.get_as::<String>("Format")?
+            && format != "lhagrid1"
+        {
+            return Err(Error::General(format!("unsupported format '{format}'")));
+        }
+
+        let mut subgrids = Vec::new();
+        let mut block: Vec<(usize, &str)> = Vec::new();
+
+        for (index, line) in lines {
+            if line.trim() == "---" {
+                if !block.is_empty() {
+                    subgrids.push(parse_subgrid(&block)?);
+                    block.clear();
+                }
+            } else if !line.trim().is_empty() {
+                block.push((index, line));
+            }
+        }
+
+        // the last subgrid must be terminated by a separator
+        if let Some(&(index, _)) = block.first() {
+            return Err(Error::General(format!(
+                "line {}: subgrid is not terminated by '---'",
+                index + 1
+            )));
+        }
+
+        Ok(Self { info, subgrids })
+    }
+}
+
+fn parse_line<T: FromStr>((index, line): (usize, &str)) -> Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    line.split_whitespace()
+        .map(|token| {
+            token.parse().map_err(|err| {
+                Error::General(format!(
+                    "line {}: invalid number '{token}': {err}",
+                    index + 1
+                ))
+            })
+        })
+        .collect()
+}
+
+fn parse_subgrid(block: &[(usize, &str)]) -> Result<SubGrid> {
+    if block.len() < 3 {
+        return Err(Error::General(format!(
+            "line {}: subgrid needs lines with x knots, Q knots and flavours",
+            block[0].0 + 1
+        )));
+    }
+
+    let x = parse_line(block[0])?;
+    let q = parse_line(block[1])?;
+    let flavors: Vec<i32> = parse_line(block[2])?;
+    let mut values = Vec::with_capacity(x.len() * q.len() * flavors.len());
+
+    for &line in &block[3..] {
+        let row: Vec<f64> = parse_line(line)?;
+
+        if row.len() != flavors.len() {
+            return Err(Error::General(format!(
+                "line {}: expected {} values, found {}",
+                line.0 + 1,
+                flavors.len(),
+                row.len()
+            )));
+        }
+
+        values.extend(row);
+    }
+
+    SubGrid::new(x, q, flavors, values)
+        .map_err(|err| Error::General(format!("line {}: {err}", block[0].0 + 1)))
+}
+
+/// A PDF set in the LHAPDF6 format stored in a directory, whose members are read on demand.
+#[derive(Clone, Debug)]
+pub struct GridSet {
+    name: String,
+    path: PathBuf,
+    info: Info,
+}
+
+impl GridSet {
+    /// Open the PDF set in the directory `path`. The name of the set is the name of the directory,
+    /// which must contain the file `<name>.info`.
+    ///
+    /// # Errors
+    ///
+    /// If the directory doesn't contain a readable `.info` file an error is returned.
+    pub fn open(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                Error::General(format!("'{}' is not a PDF set directory", path.display()))
+            })?
+            .to_owned();
+        let info = Info::read(&path.join(format!("{name}.info")))?;
+
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            info,
+        })
+    }
+
+    /// Return the name of the PDF set.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the directory of the PDF set.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the metadata of the PDF set.
+    #[must_use]
+    pub const fn info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Return the path of the file of member `member`.
+    #[must_use]
+    pub fn member_path(&self, member: usize) -> PathBuf {
+        self.path.join(format!("{}_{member:04}.dat", self.name))
+    }
+
+    /// Read the member with index `member`.
+    ///
+    /// # Errors
+    ///
+    /// If the member doesn't exist or is malformed an error is returned.
+    pub fn member(&self, member: usize) -> Result<Member> {
+        let num_members = self.info.num_members()?;
+
+        if member >= num_members {
+            return Err(Error::General(format!(
+                "PDF {}/{member} is out of the member range of set {}",
+                self.name, self.name
+            )));
+        }
+
+        Member::read(&self.member_path(member))
+    }
+
+    /// Read and validate every member, see [`Member::validate`]. Also checks that the flavours of
+    /// each member agree with the metadata of the set.
+    ///
+    /// # Errors
+    ///
+    /// The first problem that is found is returned as an error.
+    pub fn validate(&self) -> Result<()> {
+        let flavors = self.info.flavors()?;
+
+        for index in 0..self.info.num_members()? {
+            let member = self.member(index)?;
+
+            member.validate().map_err(|err| {
+                Error::General(format!("{}: {err}", self.member_path(index).display()))
+            })?;
+
+            if let Some(subgrid) = member.subgrids().first() {
+                let mut member_flavors = subgrid.flavors().to_vec();
+                let mut set_flavors = flavors.clone();
+                member_flavors.sort_unstable();
+                set_flavors.sort_unstable();
+
+                if member_flavors != set_flavors {
+                    return Err(Error::General(format!(
+                        "{}: flavours differ from the ones in the metadata",
+                        self.member_path(index).display()
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_grid() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/TestGrid")
+    }
+
+    #[test]
+    fn read_info() -> Result<()> {
+        let set = GridSet::open(&test_grid())?;
+        let info = set.info();
+
+        assert_eq!(set.name(), "TestGrid");
+        assert_eq!(info.num_members()?, 3);
+        assert_eq!(info.flavors()?, [-3, -2, -1, 1, 2, 3, 21]);
+        assert_eq!(info.data_version()?, Some(1));
+        assert_eq!(info.error_type()?.as_deref(), Some("replicas"));
+        assert_eq!(
+            info.get_as::<String>("SetDesc")?.as_deref(),
+            Some("Small grid for testing the grid reader, not a physical PDF")
+        );
+        assert_eq!(info.get_as::<f64>("QMax")?, Some(1000.0));
+        assert_eq!(info.get_as::<f64>("IDontExist")?, None);
+
+        // this entry spans two lines
+        let qs: Vec<f64> = info.get_list("AlphaS_Qs")?.unwrap();
+        assert_eq!(qs.len(), 9);
+        assert!((qs[8] - 1000.0).abs() < 1e-12);
+
+        assert_eq!(
+            info.get_as::<i32>("SetDesc").unwrap_err().to_string(),
+            "invalid value '\"Small grid for testing the grid reader, not a physical PDF\"' of 'SetDesc': invalid digit found in string"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_member() -> Result<()> {
+        let set = GridSet::open(&test_grid())?;
+        let member = set.member(0)?;
+
+        assert_eq!(member.info().get("PdfType"), Some("central"));
+        assert_eq!(member.subgrids().len(), 2);
+
+        let subgrid = &member.subgrids()[1];
+
+        assert_eq!(subgrid.x().len(), 9);
+        assert_eq!(subgrid.q(), [4.92, 10.0, 30.0, 100.0, 1000.0]);
+        assert_eq!(subgrid.flavors(), [-3, -2, -1, 1, 2, 3, 21]);
+        assert_eq!(subgrid.values().len(), 9 * 5 * 7);
+        // x = 1 has vanishing PDFs
+        assert!(subgrid.value(8, 4, 6).abs() < 1e-15);
+        // subgrids join continuously
+        assert!((subgrid.value(0, 0, 6) - 2.071_357_73e1).abs() < 1e-12);
+        assert!((member.subgrids()[0].value(0, 3, 6) - 2.071_357_73e1).abs() < 1e-12);
+
+        set.validate()?;
+
+        assert_eq!(
+            set.member(3).unwrap_err().to_string(),
+            "PDF TestGrid/3 is out of the member range of set TestGrid"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_members() {
+        let header = "PdfType: central\nFormat: lhagrid1\n---\n";
+
+        assert_eq!(
+            format!("{header}1e-3 1\n1 2\n21\n1\n2\n3\n---\n")
+                .parse::<Member>()
+                .unwrap_err()
+                .to_string(),
+            "line 4: subgrid with 2 x knots, 2 Q knots and 1 flavours needs 4 values, found 3"
+        );
+
+        assert_eq!(
+            format!("{header}1e-3 1\n1 2\n21 1\n1 2\n")
+                .parse::<Member>()
+                .unwrap_err()
+                .to_string(),
+            "line 4: subgrid is not terminated by '---'"
+        );
+
+        assert_eq!(
+            "Format: lhagrid2\n---\n"
+                .parse::<Member>()
+                .unwrap_err()
+                .to_string(),
+            "unsupported format 'lhagrid2'"
+        );
+
+        let member: Member = format!("{header}1 1e-3\n1 2\n21\n1\n2\n3\n4\n---\n")
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            member.validate().unwrap_err().to_string(),
+            "subgrid 0: x knots are not strictly increasing"
+        );
+    }
+
+    #[test]
+    fn info_round_trip() -> Result<()> {
+        let mut info = Info::default();
+        info.set("SetDesc", "\"a test\"");
+        info.set("NumMembers", 1);
+        info.set_list("Flavors", &[21, 1, 2]);
+
+        let string = info.to_string();
+
+        assert_eq!(
+            string,
+            "SetDesc: \"a test\"\nNumMembers: 1\nFlavors: [21, 1, 2]\n"
+        );
+        assert_eq!(string.parse::<Info>()?, info);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Adaptive numerical integration of smooth functions.
+
+/// Nodes of the 15-point Gauss-Kronrod rule on `[-1, 1]`, with the nodes of the embedded 7-point
+/// Gauss rule at the odd indices.
+const NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_5,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_48,
+    0.0,
+];
+
+/// Weights of the 15-point Kronrod rule belonging to [`NODES`].
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_224,
+    0.063_092_092_629_978_56,
+    0.104_790_010_322_250_19,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_42,
+    0.204_432_940_075_298_89,
+    0.209_482_141_084_727_82,
+];
+
+/// Weights of the 7-point Gauss rule belonging to the odd indices of [`NODES`].
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_64,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+/// Maximum number of subintervals.
+const MAX_INTERVALS: usize = 500;
+
+/// Integrate `f` over `[a, b]` with the 15-point rule, returning the result and the difference to
+/// the embedded 7-point rule as an estimate of the error.
+fn gauss_kronrod(f: &impl Fn(f64) -> f64, a: f64, b: f64) -> (f64, f64) {
+    let center = f64::midpoint(a, b);
+    let half = 0.5 * (b - a);
+    let value = f(center);
+    let mut kronrod = KRONROD_WEIGHTS[7] * value;
+    let mut gauss = GAUSS_WEIGHTS[3] * value;
+
+    for (index, (&node, &weight)) in NODES[..7].iter().zip(&KRONROD_WEIGHTS).enumerate() {
+        let sum = f(half.mul_add(-node, center)) + f(half.mul_add(node, center));
+        kronrod += weight * sum;
+
+        if index % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[index / 2] * sum;
+        }
+    }
+
+    (kronrod * half, ((kronrod - gauss) * half).abs())
+}
+
+/// Integrate `f` over `[a, b]` until the estimated error is smaller than `tolerance` times the
+/// absolute value of the result, always bisecting the subinterval with the largest error.
+pub fn integrate(f: impl Fn(f64) -> f64, a: f64, b: f64, tolerance: f64) -> f64 {
+    let (result, error) = gauss_kronrod(&f, a, b);
+    let mut intervals = vec![(a, b, result, error)];
+
+    while intervals.len() < MAX_INTERVALS {
+        let (result, error) = intervals
+            .iter()
+            .fold((0.0, 0.0), |(result, error), interval| {
+                (result + interval.2, error + interval.3)
+            });
+
+        // the absolute tolerance catches integrals that vanish
+        if error <= tolerance * f64::abs(result) || error < f64::MIN_POSITIVE {
+            break;
+        }
+
+        let index = intervals
+            .iter()
+            .enumerate()
+            .max_by(|(_, lhs), (_, rhs)| lhs.3.total_cmp(&rhs.3))
+            .map(|(index, _)| index)
+            // UNWRAP: `intervals` is never empty
+            .unwrap();
+        let (a, b, _, _) = intervals.swap_remove(index);
+        let center = f64::midpoint(a, b);
+        let (left, left_error) = gauss_kronrod(&f, a, center);
+        let (right, right_error) = gauss_kronrod(&f, center, b);
+
+        intervals.push((a, center, left, left_error));
+        intervals.push((center, b, right, right_error));
+    }
+
+    intervals.iter().map(|interval| interval.2).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integrate_functions() {
+        type Case = (fn(f64) -> f64, f64, f64, f64);
+
+        let cases: [Case; 4] = [
+            (|x| x.powi(3), 0.0, 2.0, 4.0),
+            (f64::exp, 0.0, 1.0, std::f64::consts::E - 1.0),
+            // integrable singularity at zero
+            (|x| 1.0 / x.sqrt(), 0.0, 1.0, 2.0),
+            (f64::sin, 0.0, 2.0 * std::f64::consts::PI, 0.0),
+        ];
+
+        for (f, a, b, expected) in cases {
+            let result = integrate(f, a, b, 1e-10);
+            assert!(
+                (result - expected).abs() <= 1e-9 * expected.abs().max(1e-6),
+                "{result} != {expected}"
+            );
+        }
+    }
+}
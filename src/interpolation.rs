@@ -0,0 +1,93 @@
+//! Interpolation and extrapolation strategies of grid PDFs.
+
+use super::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Strategy to interpolate PDF grids between their knots. Its string representation is the name
+/// LHAPDF's `Interpolator` setting accepts.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Interpolator {
+    /// Cubic interpolation in `log(x)` and `log(Q2)`, the default of LHAPDF.
+    #[serde(rename = "logcubic", alias = "logbicubic")]
+    LogBicubic,
+    /// Linear interpolation in `log(x)` and `log(Q2)`.
+    #[serde(rename = "log", alias = "loglinear", alias = "logbilinear")]
+    LogBilinear,
+    /// Cubic interpolation in `x` and `Q2`.
+    #[serde(rename = "cubic", alias = "bicubic")]
+    Bicubic,
+    /// Linear interpolation in `x` and `Q2`.
+    #[serde(rename = "linear", alias = "bilinear")]
+    Bilinear,
+    /// Value of the nearest knot.
+    #[serde(rename = "nearest")]
+    Nearest,
+}
+
+/// Strategy to evaluate PDFs outside of their grids.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Extrapolator {
+    /// Continue the interpolation beyond the grid boundaries, the default of LHAPDF.
+    #[serde(rename = "continuation")]
+    Continuation,
+    /// Value at the nearest point on the grid boundary.
+    #[serde(rename = "nearest")]
+    Nearest,
+    /// Throw an error.
+    #[serde(rename = "error")]
+    Error,
+}
+
+impl Display for Interpolator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::LogBicubic => "logcubic",
+            Self::LogBilinear => "log",
+            Self::Bicubic => "cubic",
+            Self::Bilinear => "linear",
+            Self::Nearest => "nearest",
+        })
+    }
+}
+
+impl FromStr for Interpolator {
+    type Err = Error;
+
+    /// Parse the names LHAPDF accepts for interpolators, and the names of the variants.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "logcubic" | "logbicubic" => Ok(Self::LogBicubic),
+            "log" | "loglinear" | "logbilinear" => Ok(Self::LogBilinear),
+            "cubic" | "bicubic" => Ok(Self::Bicubic),
+            "linear" | "bilinear" => Ok(Self::Bilinear),
+            "nearest" => Ok(Self::Nearest),
+            _ => Err(Error::General(format!("unknown interpolator '{s}'"))),
+        }
+    }
+}
+
+impl Display for Extrapolator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Continuation => "continuation",
+            Self::Nearest => "nearest",
+            Self::Error => "error",
+        })
+    }
+}
+
+impl FromStr for Extrapolator {
+    type Err = Error;
+
+    /// Parse the names LHAPDF accepts for extrapolators.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "continuation" => Ok(Self::Continuation),
+            "nearest" => Ok(Self::Nearest),
+            "error" => Ok(Self::Error),
+            _ => Err(Error::General(format!("unknown extrapolator '{s}'"))),
+        }
+    }
+}
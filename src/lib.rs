@@ -3,48 +3,423 @@
 
 //! (Unofficial) Rust wrapper for the [LHAPDF](https://lhapdf.hepforge.org) C++ library.
 
+#[cfg(feature = "lhapdf")]
+mod alphas;
+mod backend;
+pub mod basis;
+#[cfg(feature = "lhapdf")]
+mod cache;
+pub mod conversions;
 mod error;
+#[cfg(feature = "lhapdf")]
 mod ffi;
+pub mod grid;
+mod integration;
+mod interpolation;
+#[cfg(feature = "managed")]
+mod lockfile;
+pub mod luminosity;
 #[cfg(feature = "managed")]
 mod manager;
+#[cfg(any(feature = "lhapdf", feature = "native"))]
+mod memory;
+pub mod moments;
+#[cfg(feature = "native")]
+mod native;
+#[cfg(feature = "lhapdf")]
 mod unmanaged;
+mod writer;
 
-#[cfg(not(feature = "managed"))]
+#[cfg(all(feature = "lhapdf", not(feature = "managed")))]
 mod manager {
     pub use super::unmanaged::*;
 }
 
+#[cfg(feature = "lhapdf")]
+use basis::Basis;
+#[cfg(feature = "lhapdf")]
 use cxx::{CxxVector, UniquePtr, let_cxx_string};
+#[cfg(feature = "lhapdf")]
+use moments::SumRuleViolation;
+#[cfg(feature = "lhapdf")]
 use std::fmt::{self, Formatter};
+#[cfg(lhapdf_thread_unsafe)]
+use std::sync::{Mutex, PoisonError};
 
+#[cfg(feature = "lhapdf")]
+pub use alphas::{AlphaS, AlphaSRef, AlphaSType};
+pub use backend::PdfBackend;
 pub use error::{Error, Result};
+#[cfg(feature = "lhapdf")]
 pub use ffi::PdfUncertainty;
+pub use interpolation::{Extrapolator, Interpolator};
+#[cfg(feature = "managed")]
+pub use lockfile::{LockedSet, Lockfile};
+#[cfg(feature = "managed")]
+pub use manager::{Config, IndexConflict, InstalledSet, LhapdfSettings, RemoteSet, SetUpdate};
+#[cfg(feature = "native")]
+pub use native::NativePdf;
+pub use writer::PdfSetWriter;
 
 /// CL percentage for a Gaussian 1-sigma.
 pub const CL_1_SIGMA: f64 = 68.268_949_213_708_58;
 
+/// Version of LHAPDF found by `pkg-config` when this crate was built. With the `docs-only` feature
+/// LHAPDF isn't used and this is `None`.
+#[cfg(feature = "lhapdf")]
+pub const LHAPDF_BUILD_VERSION: Option<&str> = option_env!("MANAGED_LHAPDF_LHAPDF_VERSION");
+
+/// Whether the evaluation of PDFs and the strong coupling is serialized, because the LHAPDF
+/// version this crate was built with isn't thread safe. See [`LHAPDF_BUILD_VERSION`].
+#[cfg(feature = "lhapdf")]
+pub const SERIALIZED_EVALUATION: bool = cfg!(lhapdf_thread_unsafe);
+
+/// Mutex that serializes the evaluations of all PDFs.
+#[cfg(lhapdf_thread_unsafe)]
+static EVALUATION: Mutex<()> = Mutex::new(());
+
+/// Call `f`, which evaluates a PDF or a strong coupling with LHAPDF, while holding a global lock
+/// if LHAPDF isn't thread safe.
+#[cfg(feature = "lhapdf")]
+fn serialized<T>(f: impl FnOnce() -> T) -> T {
+    // a panic while evaluating, for instance because of an invalid `x`, doesn't leave LHAPDF in an
+    // inconsistent state
+    #[cfg(lhapdf_thread_unsafe)]
+    let _guard = EVALUATION.lock().unwrap_or_else(PoisonError::into_inner);
+
+    f()
+}
+
+/// Set the directories in which LHAPDF searches for PDF sets. This must be called before any PDF
+/// set is loaded with LHAPDF or registered with [`register_set`]; calling it again has no effect.
+///
+/// The temporary directory of [`register_set`] is searched first. With the `managed` feature it's
+/// followed by the directories of the configuration file, which replace the ones of the
+/// environment; without it, it's followed by the directories LHAPDF searched before.
+///
+/// # Safety
+///
+/// LHAPDF reads its search paths from the environment variable `LHAPDF_DATA_PATH`, which this
+/// function modifies. It must therefore be called before other threads are started that may read
+/// or write the environment, for instance at the beginning of `main`, see [`std::env::set_var`].
+#[cfg(feature = "lhapdf")]
+pub unsafe fn init() {
+    // SAFETY: guaranteed by the caller
+    unsafe { manager::init() };
+}
+
 /// Convert an LHAID to an LHAPDF set name and member ID.
+#[cfg(feature = "lhapdf")]
 #[must_use]
 pub fn lookup_pdf(lhaid: i32) -> Option<(String, i32)> {
     manager::pdf_name_and_member_via_lhaid(lhaid)
 }
 
+/// Return the LHAIDs that the configured sources of `pdfsets.index` map to different PDF sets.
+/// For each of them the set from the source listed first is used.
+///
+/// # Errors
+///
+/// If the local copy of a source can not be read an error is returned.
+#[cfg(feature = "managed")]
+pub fn pdfsets_index_conflicts() -> Result<Vec<IndexConflict>> {
+    manager::pdfsets_index_conflicts()
+}
+
+/// Return the locally downloaded PDF sets for which `pdfsets.index` lists a newer `DataVersion`.
+/// Sets with a pinned version are never reported.
+///
+/// # Errors
+///
+/// If `pdfsets.index` could not be refreshed or the metadata of a PDF set could not be read an
+/// error is returned.
+#[cfg(feature = "managed")]
+pub fn check_updates() -> Result<Vec<SetUpdate>> {
+    manager::check_updates()
+}
+
+/// Download the latest version of the PDF set `name`, replacing the local copy. The old version
+/// stays in place until the new one is completely unpacked.
+///
+/// Members of the set are removed from the cache of [`Pdf::cached`], so that they're loaded again
+/// from the new version.
+///
+/// # Errors
+///
+/// If the set was not found, its download failed or it doesn't have the pinned version, an error
+/// is returned.
+#[cfg(feature = "managed")]
+pub fn update_set(name: &str) -> Result<()> {
+    manager::update_set(name)?;
+    cache::remove_set(name);
+
+    Ok(())
+}
+
+/// Download the PDF set `name` if it isn't available locally, and return the directory
+/// containing it.
+///
+/// # Errors
+///
+/// If the set was not found locally or at any of the configured URLs an error is returned.
+#[cfg(feature = "managed")]
+pub fn install_set(name: &str) -> Result<std::path::PathBuf> {
+    manager::install_set(name)
+}
+
+/// Remove the PDF set `name` from the download directory. Sets in any of the other directories
+/// are never removed. Returns `false` if the download directory doesn't contain the set.
+///
+/// Members of the set are removed from the cache of [`Pdf::cached`], but [`Pdf`]s that are still
+/// alive keep their data, and LHAPDF keeps the metadata of the set if it was loaded before.
+///
+/// # Errors
+///
+/// If `name` isn't a valid set name or the set could not be removed an error is returned.
+#[cfg(feature = "managed")]
+pub fn remove_set(name: &str) -> Result<bool> {
+    let removed = manager::remove_set(name)?;
+    cache::remove_set(name);
+
+    Ok(removed)
+}
+
+/// Return the PDF sets found in the local data directories, sorted by name. If directories
+/// contain sets with the same name, the one LHAPDF uses is returned.
+///
+/// Sets whose metadata can't be read are skipped.
+///
+/// # Errors
+///
+/// If a data directory could not be read an error is returned.
+#[cfg(feature = "managed")]
+pub fn installed_sets() -> Result<Vec<InstalledSet>> {
+    manager::installed_sets()
+}
+
+/// Return the PDF sets listed in the local copy of `pdfsets.index`, ordered by LHAID.
+///
+/// # Errors
+///
+/// If the local copy of `pdfsets.index` could not be read an error is returned.
+#[cfg(feature = "managed")]
+pub fn remote_sets() -> Result<Vec<RemoteSet>> {
+    manager::remote_sets()
+}
+
+/// Refresh the local copy of `pdfsets.index` from all its sources, regardless of its age, and
+/// return the LHAIDs that the sources map to different PDF sets, like
+/// [`pdfsets_index_conflicts`].
+///
+/// # Errors
+///
+/// If a source could not be downloaded and there's no older copy of it an error is returned.
+#[cfg(feature = "managed")]
+pub fn update_pdfsets_index() -> Result<Vec<IndexConflict>> {
+    manager::update_pdfsets_index()
+}
+
+/// Return a [`Lockfile`] recording every PDF set that was loaded so far, together with its
+/// `DataVersion`, directory, content hash and the members that were used.
+///
+/// # Errors
+///
+/// If the files of a loaded PDF set could not be read an error is returned.
+#[cfg(feature = "managed")]
+pub fn lockfile() -> Result<Lockfile> {
+    manager::lockfile()
+}
+
+/// Verify every PDF set that was or will be loaded against `lockfile`. After calling this
+/// function, loading a PDF set that isn't recorded in `lockfile` or whose `DataVersion` or content
+/// differs fails.
+///
+/// # Errors
+///
+/// If a PDF set loaded before calling this function fails the verification an error is returned.
+#[cfg(feature = "managed")]
+pub fn verify_against(lockfile: Lockfile) -> Result<()> {
+    manager::verify_against(lockfile)
+}
+
+/// Register the PDF set written by `writer` under its name, so that it can be loaded like any
+/// other set without calling [`PdfSetWriter::write`]. A set registered before with the same name
+/// is replaced.
+///
+/// With the `native` feature [`NativePdf`] evaluates the set directly from memory. For [`Pdf`]
+/// and [`PdfSet`] the set is written into a temporary directory of this process, which is searched
+/// before all other directories. LHAPDF caches the metadata of sets it has loaded, so replacing a
+/// set that was already loaded by LHAPDF doesn't change its metadata. Members of a replaced set
+/// are removed from the cache of [`Pdf::cached`].
+///
+/// # Errors
+///
+/// If `writer` doesn't have any subgrids or members, or the set could not be written into the
+/// temporary directory, an error is returned. With the `lhapdf` feature an error is also returned
+/// if [`init`] wasn't called.
+#[cfg(any(feature = "lhapdf", feature = "native"))]
+pub fn register_set(writer: &PdfSetWriter) -> Result<()> {
+    memory::register_set(writer)?;
+    #[cfg(feature = "lhapdf")]
+    cache::remove_set(writer.name());
+
+    Ok(())
+}
+
+/// Remove the PDF set `name` registered with [`register_set`]. Returns `false` if no set with
+/// this name was registered.
+///
+/// The temporary directory of the process is removed together with the last registered set, and
+/// the members of the set are removed from the cache of [`Pdf::cached`].
+///
+/// # Errors
+///
+/// If the temporary copy of the set could not be removed an error is returned.
+#[cfg(any(feature = "lhapdf", feature = "native"))]
+pub fn unregister_set(name: &str) -> Result<bool> {
+    let removed = memory::unregister_set(name)?;
+    #[cfg(feature = "lhapdf")]
+    cache::remove_set(name);
+
+    Ok(removed)
+}
+
+/// Return the version of the LHAPDF library this crate is linked against as major, minor and
+/// patch version. Missing or non-numeric parts of the version are zero.
+#[cfg(feature = "lhapdf")]
+#[must_use]
+pub fn lhapdf_version() -> (u32, u32, u32) {
+    let_cxx_string!(version = "");
+    ffi::lhapdf_version(version.as_mut());
+    let version = version.to_string_lossy();
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default());
+
+    (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or_default(),
+    )
+}
+
+/// Return the installation prefix of LHAPDF reported by `pkg-config` when this crate was built,
+/// or `None` if it isn't known.
+#[cfg(feature = "lhapdf")]
+#[must_use]
+pub fn lhapdf_prefix() -> Option<std::path::PathBuf> {
+    option_env!("MANAGED_LHAPDF_LHAPDF_PREFIX").map(Into::into)
+}
+
+/// Return the directories in which LHAPDF searches for PDF sets, in the order in which they are
+/// searched.
+///
+/// Unlike [`data_paths`] these include the directories LHAPDF searches on its own, for instance
+/// its installation directory.
+#[cfg(feature = "lhapdf")]
+#[must_use]
+pub fn lhapdf_paths() -> Vec<std::path::PathBuf> {
+    manager::search_paths()
+}
+
+/// Add `path` to the end of the directories that are searched for PDF sets, which are listed by
+/// [`lhapdf_paths`].
+///
+/// # Errors
+///
+/// If [`init`] wasn't called an error is returned.
+///
+/// # Safety
+///
+/// Like [`init`] this modifies the environment variable `LHAPDF_DATA_PATH`. This function must
+/// therefore not be called while other threads may read or write the environment, see
+/// [`std::env::set_var`].
+#[cfg(feature = "lhapdf")]
+pub unsafe fn add_data_path(path: &std::path::Path) -> Result<()> {
+    // SAFETY: guaranteed by the caller
+    unsafe { manager::paths_append(path) }
+}
+
+/// Return the directories that this crate added to the search paths of LHAPDF, in the order in
+/// which they are searched: the ones set by [`init`], followed by the ones given to
+/// [`add_data_path`].
+#[cfg(feature = "lhapdf")]
+#[must_use]
+pub fn data_paths() -> Vec<std::path::PathBuf> {
+    manager::data_paths()
+}
+
+/// Return whether LHAPDF is linked statically, which is enabled with the `static` feature.
+#[must_use]
+pub const fn is_static_build() -> bool {
+    cfg!(feature = "static")
+}
+
+/// Set the memory budget in bytes of the cache used by [`Pdf::cached`] and [`PdfSet::members`].
+///
+/// If the cached members need more memory, the least recently used ones are removed from the
+/// cache. The default budget is 1 GiB.
+#[cfg(feature = "lhapdf")]
+pub fn set_cache_budget(bytes: u64) {
+    cache::set_budget(bytes);
+}
+
+/// Return the memory budget in bytes of the cache used by [`Pdf::cached`].
+#[cfg(feature = "lhapdf")]
+#[must_use]
+pub fn cache_budget() -> u64 {
+    cache::budget()
+}
+
+/// Return the memory in bytes used by the members in the cache of [`Pdf::cached`]. This is an
+/// estimate based on the size of the members' data files.
+#[cfg(feature = "lhapdf")]
+#[must_use]
+pub fn cache_size() -> u64 {
+    cache::size()
+}
+
+/// Remove all members from the cache used by [`Pdf::cached`]. Members that are still used
+/// elsewhere are freed when their last [`Arc`](std::sync::Arc) is dropped.
+#[cfg(feature = "lhapdf")]
+pub fn clear_cache() {
+    cache::clear();
+}
+
 /// Convenient way to set the verbosity level.
+#[cfg(feature = "lhapdf")]
 pub fn set_verbosity(verbosity: i32) {
     manager::set_verbosity(verbosity);
 }
 
 /// Convenient way to get the current verbosity level.
+#[cfg(feature = "lhapdf")]
 #[must_use]
 pub fn verbosity() -> i32 {
     manager::verbosity()
 }
 
+/// Return the name LHAPDF uses in metadata keys for the quark with PDG ID `pid`.
+#[cfg(feature = "lhapdf")]
+fn quark_name(pid: i32) -> Result<&'static str> {
+    match pid.abs() {
+        1 => Ok("Down"),
+        2 => Ok("Up"),
+        3 => Ok("Strange"),
+        4 => Ok("Charm"),
+        5 => Ok("Bottom"),
+        6 => Ok("Top"),
+        _ => Err(Error::General(format!("PDG ID {pid} is not a quark"))),
+    }
+}
+
 /// Wrapper to an LHAPDF object of the type `LHAPDF::PDF`.
+#[cfg(feature = "lhapdf")]
 pub struct Pdf {
     ptr: UniquePtr<ffi::PDF>,
 }
 
+#[cfg(feature = "lhapdf")]
 impl fmt::Debug for Pdf {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         // TODO: not all PDFs have an LHAID
@@ -54,6 +429,7 @@ impl fmt::Debug for Pdf {
     }
 }
 
+#[cfg(feature = "lhapdf")]
 impl Pdf {
     /// Constructor. Create a new PDF with the given `lhaid` ID code.
     ///
@@ -79,6 +455,18 @@ impl Pdf {
         manager::pdf_with_setname_and_member(setname, member).map(|ptr| Self { ptr })
     }
 
+    /// Return the PDF with the given PDF `setname` and `member` ID from a process-wide cache,
+    /// loading it only if it isn't cached. Repeated calls return handles to the same PDF.
+    ///
+    /// The memory used by the cache is bounded by [`set_cache_budget`].
+    ///
+    /// # Errors
+    ///
+    /// If the PDF isn't cached and could not be loaded an error is returned.
+    pub fn cached(setname: &str, member: i32) -> Result<std::sync::Arc<Self>> {
+        cache::pdf(setname, member)
+    }
+
     /// Create a new PDF with the given PDF set name and member ID as a single string.
     ///
     /// The format of the `setname_nmem` string is `<setname>/<nmem>` where `<nmem>` must be
@@ -117,7 +505,7 @@ impl Pdf {
     /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
     #[must_use]
     pub fn xfx_q2(&self, id: i32, x: f64, q2: f64) -> f64 {
-        self.ptr.xfxQ2(id, x, q2).unwrap()
+        serialized(|| self.ptr.xfxQ2(id, x, q2)).unwrap()
     }
 
     /// Value of of the strong coupling at `q2` used by this PDF.
@@ -127,7 +515,103 @@ impl Pdf {
     /// If the value of `q2` is not within proper boundaries this method will panic.
     #[must_use]
     pub fn alphas_q2(&self, q2: f64) -> f64 {
-        self.ptr.alphasQ2(q2).unwrap()
+        serialized(|| self.ptr.alphasQ2(q2)).unwrap()
+    }
+
+    /// Return the metadata entry `key` of this PDF. If the member doesn't define it, the entry of
+    /// its set or of the global settings is used.
+    fn info_entry(&self, key: &str) -> Option<String> {
+        let_cxx_string!(cxx_key = key);
+        let_cxx_string!(value = "");
+
+        ffi::pdf_info_entry(&self.ptr, &cxx_key, value.as_mut())
+            .ok()
+            .map(|()| value.to_string_lossy().into_owned())
+    }
+
+    /// Return an error if neither the member nor the set of this PDF define any of the metadata
+    /// entries `keys`. The global settings are ignored, since `lhapdf.conf` always defines the
+    /// quark masses.
+    fn require_entry(&self, keys: &[&str]) -> Result<()> {
+        if keys.iter().any(|key| {
+            let_cxx_string!(cxx_key = *key);
+            ffi::pdf_has_local_entry(&self.ptr, &cxx_key)
+        }) {
+            Ok(())
+        } else {
+            let_cxx_string!(setname = "");
+            ffi::pdf_setname(&self.ptr, setname.as_mut());
+
+            Err(Error::General(format!(
+                "PDF set '{}' doesn't define '{}'",
+                setname.to_string_lossy(),
+                keys.join("' or '")
+            )))
+        }
+    }
+
+    /// Return the strong-coupling object used by this PDF.
+    #[must_use]
+    pub fn alphas(&self) -> AlphaSRef<'_> {
+        // the entry cascades to the global settings, which always define `MZ`
+        let mz = self
+            .info_entry("MZ")
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(alphas::DEFAULT_MZ);
+
+        AlphaSRef::new(ffi::pdf_alphas(&self.ptr), mz)
+    }
+
+    /// Return the perturbative QCD order of this PDF, where 0 is leading order.
+    ///
+    /// # Errors
+    ///
+    /// If the PDF set doesn't define `OrderQCD` an error is returned.
+    pub fn order_qcd(&self) -> Result<i32> {
+        self.require_entry(&["OrderQCD"])?;
+
+        Ok(ffi::pdf_order_qcd(&self.ptr)?)
+    }
+
+    /// Return the mass of the quark with PDG ID `pid` that this PDF was determined with.
+    ///
+    /// # Errors
+    ///
+    /// If `pid` isn't a quark or no mass is defined for it an error is returned.
+    pub fn quark_mass(&self, pid: i32) -> Result<f64> {
+        self.require_entry(&[&format!("M{}", quark_name(pid)?)])?;
+
+        Ok(ffi::pdf_quark_mass(&self.ptr, pid)?)
+    }
+
+    /// Return the flavour threshold of the quark with PDG ID `pid`. If the PDF doesn't define a
+    /// threshold, it is the quark mass.
+    ///
+    /// # Errors
+    ///
+    /// If `pid` isn't a quark or neither a threshold nor a mass is defined for it an error is
+    /// returned.
+    pub fn quark_threshold(&self, pid: i32) -> Result<f64> {
+        let name = quark_name(pid)?;
+        self.require_entry(&[&format!("Threshold{name}"), &format!("M{name}")])?;
+
+        Ok(ffi::pdf_quark_threshold(&self.ptr, pid)?)
+    }
+
+    /// Return the number of active flavours at `q2`, as determined by the flavour thresholds of the
+    /// strong coupling.
+    ///
+    /// # Errors
+    ///
+    /// If the thresholds needed to determine the number of flavours are not defined an error is
+    /// returned.
+    pub fn num_flavors_q2(&self, q2: f64) -> Result<i32> {
+        self.alphas().num_flavors_q2(q2)
+    }
+
+    /// Replace the strong-coupling object used by this PDF with `alphas`.
+    pub fn set_alphas(&mut self, alphas: AlphaS) {
+        ffi::pdf_set_alphas(self.ptr.pin_mut(), alphas.into_ptr());
     }
 
     /// Get the info class that actually stores and handles the metadata.
@@ -144,14 +628,14 @@ impl Pdf {
 
     /// Minimum valid x value for this PDF.
     #[must_use]
-    pub fn x_min(&mut self) -> f64 {
-        self.ptr.pin_mut().xMin()
+    pub fn x_min(&self) -> f64 {
+        ffi::pdf_x_min(&self.ptr)
     }
 
     /// Maximum valid x value for this PDF.
     #[must_use]
-    pub fn x_max(&mut self) -> f64 {
-        self.ptr.pin_mut().xMax()
+    pub fn x_max(&self) -> f64 {
+        ffi::pdf_x_max(&self.ptr)
     }
 
     /// Set whether the PDF will only return positive (definite) values or not.
@@ -169,6 +653,52 @@ impl Pdf {
         self.ptr.pin_mut().forcePositive()
     }
 
+    /// Return the strategy used to interpolate between the knots of the grid.
+    ///
+    /// # Panics
+    ///
+    /// If this PDF isn't based on a grid this method will panic. All PDFs loaded from files are.
+    #[must_use]
+    pub fn interpolator(&self) -> Interpolator {
+        let_cxx_string!(name = "");
+        ffi::pdf_interpolator(&self.ptr, name.as_mut()).unwrap();
+        // UNWRAP: the wrapper only returns names of known interpolators
+        name.to_string_lossy().parse().unwrap()
+    }
+
+    /// Set the strategy used to interpolate between the knots of the grid.
+    ///
+    /// # Panics
+    ///
+    /// If this PDF isn't based on a grid this method will panic. All PDFs loaded from files are.
+    pub fn set_interpolator(&mut self, interpolator: Interpolator) {
+        let_cxx_string!(name = interpolator.to_string());
+        ffi::pdf_set_interpolator(self.ptr.pin_mut(), &name).unwrap();
+    }
+
+    /// Return the strategy used to evaluate the PDF outside of its grid.
+    ///
+    /// # Panics
+    ///
+    /// If this PDF isn't based on a grid this method will panic. All PDFs loaded from files are.
+    #[must_use]
+    pub fn extrapolator(&self) -> Extrapolator {
+        let_cxx_string!(name = "");
+        ffi::pdf_extrapolator(&self.ptr, name.as_mut()).unwrap();
+        // UNWRAP: the wrapper only returns names of known extrapolators
+        name.to_string_lossy().parse().unwrap()
+    }
+
+    /// Set the strategy used to evaluate the PDF outside of its grid.
+    ///
+    /// # Panics
+    ///
+    /// If this PDF isn't based on a grid this method will panic. All PDFs loaded from files are.
+    pub fn set_extrapolator(&mut self, extrapolator: Extrapolator) {
+        let_cxx_string!(name = extrapolator.to_string());
+        ffi::pdf_set_extrapolator(self.ptr.pin_mut(), &name).unwrap();
+    }
+
     /// List of flavours defined by this [`Pdf`] set.
     #[must_use]
     pub fn flavors(&self) -> Vec<i32> {
@@ -185,16 +715,127 @@ impl Pdf {
 
         self.ptr.pin_mut().setFlavors(&vector);
     }
+
+    /// Derivative of `x * f(x)` with respect to `x` at `x` and `q2` for the given PDG ID,
+    /// calculated with finite differences.
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    #[must_use]
+    pub fn dxfx_dx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        backend::dxfx_dx(self, id, x, q2)
+    }
+
+    /// Derivative of `x * f(x)` with respect to `ln(Q^2)` at `x` and `q2` for the given PDG ID,
+    /// calculated with finite differences.
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    #[must_use]
+    pub fn dxfx_dlnq2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        backend::dxfx_dlnq2(self, id, x, q2)
+    }
+
+    /// Get the PDF `x * f(x)` values of all elements of `basis` at `x` and `q2`, ordered as
+    /// described in [`basis`]. Flavours that this PDF doesn't define are zero.
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    #[must_use]
+    pub fn xfx_q2_in_basis(&self, basis: Basis, x: f64, q2: f64) -> [f64; basis::SIZE] {
+        basis::xfx_q2(self, basis, x, q2)
+    }
+
+    /// Calculate the Mellin moment `∫ x^(n - 1) f(x) dx` of the flavour `id` at `q2`, integrating
+    /// over `x` from [`Pdf::x_min`] to `1`.
+    ///
+    /// # Panics
+    ///
+    /// If the value of `q2` is not within proper boundaries this method will panic.
+    #[must_use]
+    pub fn moment(&self, id: i32, n: i32, q2: f64) -> f64 {
+        moments::moment(self, id, n, q2)
+    }
+
+    /// Calculate the sum of the momentum fractions `∫ x f(x) dx` of all flavours at `q2`, which
+    /// should be one.
+    ///
+    /// # Panics
+    ///
+    /// If the value of `q2` is not within proper boundaries this method will panic.
+    #[must_use]
+    pub fn momentum_sum_rule(&self, q2: f64) -> f64 {
+        moments::momentum_sum_rule(self, q2)
+    }
+
+    /// Calculate the number of valence quarks `∫ (f_q(x) - f_qbar(x)) dx` of the quark with PDG
+    /// ID `pid` at `q2`.
+    ///
+    /// # Panics
+    ///
+    /// If the value of `q2` is not within proper boundaries this method will panic.
+    #[must_use]
+    pub fn valence_sum_rule(&self, pid: i32, q2: f64) -> f64 {
+        moments::valence_sum_rule(self, pid, q2)
+    }
 }
 
+#[cfg(feature = "lhapdf")]
+impl PdfBackend for Pdf {
+    fn xfx_q2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        Self::xfx_q2(self, id, x, q2)
+    }
+
+    fn alphas_q2(&self, q2: f64) -> f64 {
+        Self::alphas_q2(self, q2)
+    }
+
+    fn flavors(&self) -> Vec<i32> {
+        Self::flavors(self)
+    }
+
+    fn x_min(&self) -> f64 {
+        Self::x_min(self)
+    }
+
+    fn x_max(&self) -> f64 {
+        Self::x_max(self)
+    }
+
+    fn q2_min(&self) -> f64 {
+        ffi::pdf_q2_min(&self.ptr)
+    }
+
+    fn q2_max(&self) -> f64 {
+        ffi::pdf_q2_max(&self.ptr)
+    }
+
+    fn dxfx_dx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        Self::dxfx_dx(self, id, x, q2)
+    }
+
+    fn dxfx_dlnq2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        Self::dxfx_dlnq2(self, id, x, q2)
+    }
+}
+
+// SAFETY: LHAPDF's `PDF` isn't bound to a thread, and the evaluation methods called through `&self`
+// are either thread safe or serialized, see `serialized`
+#[cfg(feature = "lhapdf")]
 unsafe impl Send for Pdf {}
+#[cfg(feature = "lhapdf")]
 unsafe impl Sync for Pdf {}
 
 /// Class for PDF set metadata and manipulation.
+#[cfg(feature = "lhapdf")]
 pub struct PdfSet {
     ptr: UniquePtr<ffi::PDFSet>,
 }
 
+#[cfg(feature = "lhapdf")]
 impl fmt::Debug for PdfSet {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         // TODO: a PDF set may not have an LHAPDF ID
@@ -204,6 +845,7 @@ impl fmt::Debug for PdfSet {
     }
 }
 
+#[cfg(feature = "lhapdf")]
 impl PdfSet {
     /// Constructor from a set name.
     ///
@@ -250,6 +892,19 @@ impl PdfSet {
             .collect::<Result<Vec<_>>>()
     }
 
+    /// Return an iterator over all PDFs in this set, which loads each member only when it is
+    /// reached. The members are taken from the cache of [`Pdf::cached`], so unlike
+    /// [`mk_pdfs`](Self::mk_pdfs) the memory of a large set is bounded by [`set_cache_budget`].
+    #[must_use]
+    pub fn members(&self) -> impl ExactSizeIterator<Item = Result<std::sync::Arc<Pdf>>> {
+        let setname = self.name();
+
+        // UNWRAP: if we can't convert a `usize` to an `i32`, then we probably got too many members
+        // indicating a bug somewher
+        (0..i32::try_from(self.ptr.size()).unwrap_or_else(|_| unreachable!()))
+            .map(move |member| Pdf::cached(&setname, member))
+    }
+
     /// PDF set name.
     #[must_use]
     pub fn name(&self) -> String {
@@ -297,20 +952,135 @@ impl PdfSet {
     ) -> Result<PdfUncertainty> {
         Ok(ffi::pdf_uncertainty(&self.ptr, values, cl, alternative)?)
     }
+
+    /// Return the sum rules that the members of this set violate at `q2`.
+    ///
+    /// The momentum sum rule and the valence sum rules of the up, down and strange quarks are
+    /// checked for every member, and reported if they deviate by more than `tolerance` from their
+    /// expected values. The integrals only cover `x` values down to [`Pdf::x_min`].
+    ///
+    /// # Errors
+    ///
+    /// If a member of this set can not be loaded an error is returned.
+    ///
+    /// # Panics
+    ///
+    /// If the value of `q2` is not within proper boundaries this method will panic.
+    pub fn sum_rule_violations(&self, q2: f64, tolerance: f64) -> Result<Vec<SumRuleViolation>> {
+        Ok(moments::sum_rule_violations(
+            &self.mk_pdfs()?,
+            q2,
+            tolerance,
+        ))
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(feature = "lhapdf", test))]
 mod test {
     use super::*;
+    use std::sync::Arc;
+
+    /// Set the search paths of LHAPDF, which every test using LHAPDF must do first.
+    pub fn init() {
+        // SAFETY: the environment is modified only once, with `env::set_var`, which synchronizes
+        // with the tests reading it through `std::env`. The tests reading it through LHAPDF call
+        // this function first, which blocks until the search paths are set
+        unsafe { super::init() };
+    }
+
+    /// Function tabulated by [`toy_set`].
+    fn toy_xfx(pid: i32, x: f64, _: f64) -> f64 {
+        f64::from(pid) * x.powf(-0.1) * (1.0 - x).powi(5)
+    }
+
+    /// Create and register a PDF set `name` with a single subgrid tabulating [`toy_xfx`] for down
+    /// and up quarks and the gluon. Tests using it must unregister the set when they're done.
+    fn toy_set(name: &str) -> Result<PdfSetWriter> {
+        let x = vec![1e-5, 1e-4, 1e-3, 1e-2, 0.1, 0.5, 1.0];
+        let q2 = vec![1.0, 10.0, 100.0, 1e4, 1e6];
+
+        PdfSetWriter::from_fn(name, &[(x, q2)], vec![1, 2, 21], toy_xfx)
+    }
+
+    #[test]
+    fn check_build_version() {
+        let version = LHAPDF_BUILD_VERSION.unwrap();
+
+        assert!(version.starts_with("6."));
+        assert_eq!(
+            SERIALIZED_EVALUATION,
+            version.starts_with("6.3.") || version.starts_with("6.4.")
+        );
+    }
+
+    #[test]
+    fn check_build_info() {
+        init();
+
+        let version = lhapdf_version();
+
+        assert_eq!(version.0, 6);
+        assert!(
+            LHAPDF_BUILD_VERSION
+                .unwrap()
+                .starts_with(&format!("{}.{}.", version.0, version.1))
+        );
+        assert!(lhapdf_prefix().is_some());
+        assert!(!lhapdf_paths().is_empty());
+        assert_eq!(is_static_build(), cfg!(feature = "static"));
+    }
+
+    #[test]
+    fn check_cache() -> Result<()> {
+        init();
+
+        let pdf0 = Pdf::cached("NNPDF31_nlo_as_0118_luxqed", 0)?;
+        let pdf1 = Pdf::cached("NNPDF31_nlo_as_0118_luxqed", 1)?;
+
+        assert!(Arc::ptr_eq(
+            &pdf0,
+            &Pdf::cached("NNPDF31_nlo_as_0118_luxqed", 0)?
+        ));
+        assert!(!Arc::ptr_eq(&pdf0, &pdf1));
+        assert!(cache_size() > 0);
+
+        // only the member used last fits into the cache
+        set_cache_budget(1);
+        assert_eq!(cache_budget(), 1);
+        assert!(Arc::ptr_eq(
+            &pdf1,
+            &Pdf::cached("NNPDF31_nlo_as_0118_luxqed", 1)?
+        ));
+        assert!(!Arc::ptr_eq(
+            &pdf0,
+            &Pdf::cached("NNPDF31_nlo_as_0118_luxqed", 0)?
+        ));
+
+        let pdf_set = PdfSet::new("NNPDF31_nlo_as_0118_luxqed")?;
+        let mut members = pdf_set.members();
+
+        assert_eq!(members.len(), 101);
+        assert_eq!(members.next().unwrap()?.set().name(), pdf_set.name());
+
+        clear_cache();
+        assert_eq!(cache_size(), 0);
+        set_cache_budget(1 << 30);
+
+        Ok(())
+    }
 
     #[test]
     fn set_verbosity() {
+        init();
+
         super::set_verbosity(0);
         assert_eq!(verbosity(), 0);
     }
 
     #[test]
     fn check_lookup_pdf() {
+        init();
+
         assert!(matches!(lookup_pdf(324900), Some((name, member))
             if (name == "NNPDF31_nlo_as_0118_luxqed") && (member == 0)));
         assert!(matches!(lookup_pdf(324901), Some((name, member))
@@ -318,8 +1088,118 @@ mod test {
         assert!(matches!(lookup_pdf(-1), None));
     }
 
+    #[cfg(feature = "managed")]
+    #[test]
+    fn check_pdfsets_index_conflicts() -> Result<()> {
+        init();
+
+        assert_eq!(pdfsets_index_conflicts()?, []);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "managed")]
+    #[test]
+    fn check_update_set() -> Result<()> {
+        init();
+
+        let _ = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
+
+        assert!(
+            !check_updates()?
+                .iter()
+                .any(|update| update.name == "NNPDF31_nlo_as_0118_luxqed")
+        );
+
+        assert_eq!(
+            update_set("IDontExist").unwrap_err().to_string(),
+            "PDF set 'IDontExist' was not found at any of the configured URLs"
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "managed")]
+    #[test]
+    fn check_install_and_list_sets() -> Result<()> {
+        init();
+
+        let path = install_set("NNPDF31_nlo_as_0118_luxqed")?;
+
+        assert!(
+            installed_sets()?.iter().any(|set| {
+                set.name == "NNPDF31_nlo_as_0118_luxqed" && path.ends_with(&set.name)
+            })
+        );
+        assert!(
+            remote_sets()?
+                .iter()
+                .any(|set| { set.name == "NNPDF31_nlo_as_0118_luxqed" && set.lhaid == 324900 })
+        );
+
+        assert!(!remove_set("IDontExist")?);
+        assert_eq!(
+            remove_set("../escape").unwrap_err().to_string(),
+            "'../escape' is not a valid PDF set name"
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "managed")]
+    #[test]
+    fn check_lockfile() -> Result<()> {
+        init();
+
+        let _ = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 1)?;
+
+        let lockfile = lockfile()?;
+        let set = lockfile
+            .sets
+            .iter()
+            .find(|set| set.name == "NNPDF31_nlo_as_0118_luxqed")
+            .unwrap();
+
+        assert!(set.members.contains(&1));
+        assert!(set.path.ends_with("NNPDF31_nlo_as_0118_luxqed"));
+        assert_eq!(set.sha256.len(), 64);
+        assert!(lockfile.verify(set).is_ok());
+
+        let mut modified = set.clone();
+        modified.sha256 = "0".repeat(64);
+
+        assert_eq!(
+            lockfile.verify(&modified).unwrap_err().to_string(),
+            format!(
+                "content of PDF set 'NNPDF31_nlo_as_0118_luxqed' in '{}' doesn't match the lockfile",
+                set.path.display()
+            )
+        );
+
+        assert_eq!(
+            Lockfile::default().verify(set).unwrap_err().to_string(),
+            "PDF set 'NNPDF31_nlo_as_0118_luxqed' is not recorded in the lockfile"
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "managed")]
+    #[test]
+    fn check_lhapdf_settings() {
+        init();
+
+        let settings = Config::get().lhapdf();
+
+        assert_eq!(settings.quark_mass(-4), settings.quark_mass(4));
+        assert_eq!(settings.quark_mass(21), None);
+        assert!(settings.mz() > 0.0);
+    }
+
     #[test]
     fn debug_pdf() -> Result<()> {
+        init();
+
         let pdf = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
 
         assert_eq!(format!("{:?}", pdf), "Pdf { lhaid: 324900 }");
@@ -329,8 +1209,10 @@ mod test {
 
     #[test]
     fn check_pdf() -> Result<()> {
-        let mut pdf_0 = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
-        let mut pdf_1 = Pdf::with_lhaid(324900)?;
+        init();
+
+        let pdf_0 = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
+        let pdf_1 = Pdf::with_lhaid(324900)?;
 
         let value_0 = pdf_0.xfx_q2(2, 0.5, 90.0 * 90.0);
         let value_1 = pdf_1.xfx_q2(2, 0.5, 90.0 * 90.0);
@@ -366,6 +1248,8 @@ mod test {
 
     #[test]
     fn check_setname_and_nmem() -> Result<()> {
+        init();
+
         let pdf_0 = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 1)?;
         let pdf_1 = Pdf::with_setname_and_nmem("NNPDF31_nlo_as_0118_luxqed/1")?;
 
@@ -400,6 +1284,8 @@ mod test {
 
     #[test]
     fn check_pdf_set() -> Result<()> {
+        init();
+
         let pdf_set = PdfSet::new("NNPDF31_nlo_as_0118_luxqed")?;
 
         assert!(matches!(pdf_set.entry("Particle"), Some(value) if value == "2212"));
@@ -435,6 +1321,8 @@ mod test {
 
     #[test]
     fn debug_pdf_set() -> Result<()> {
+        init();
+
         let pdf_set = PdfSet::new("NNPDF31_nlo_as_0118_luxqed")?;
 
         assert_eq!(format!("{:?}", pdf_set), "PdfSet { lhaid: 324900 }");
@@ -444,6 +1332,8 @@ mod test {
 
     #[test]
     fn check_pdf_pdfset() -> Result<()> {
+        init();
+
         let pdf_set0 = PdfSet::new("NNPDF31_nlo_as_0118_luxqed")?;
         let pdf_set1 = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?.set();
 
@@ -455,6 +1345,8 @@ mod test {
 
     #[test]
     fn force_positive() -> Result<()> {
+        init();
+
         let mut pdf = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 1)?;
 
         assert_eq!(pdf.force_positive(), 0);
@@ -467,6 +1359,8 @@ mod test {
 
     #[test]
     fn set_flavors() {
+        init();
+
         let mut pdf = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0).unwrap();
 
         assert_eq!(pdf.flavors(), &[-5, -4, -3, -2, -1, 1, 2, 3, 4, 5, 21, 22]);
@@ -476,8 +1370,204 @@ mod test {
         assert_eq!(pdf.flavors(), &[-5, -4, -3, -2, -1, 1, 2, 3, 4, 5, 21]);
     }
 
+    #[test]
+    fn set_interpolator_and_extrapolator() -> Result<()> {
+        init();
+
+        let mut pdf = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
+
+        assert_eq!(pdf.interpolator(), Interpolator::LogBicubic);
+        assert_eq!(pdf.extrapolator(), Extrapolator::Continuation);
+
+        let value = pdf.xfx_q2(21, 1e-10, 90.0 * 90.0);
+
+        pdf.set_interpolator(Interpolator::LogBilinear);
+        pdf.set_extrapolator(Extrapolator::Nearest);
+
+        assert_eq!(pdf.interpolator(), Interpolator::LogBilinear);
+        assert_eq!(pdf.extrapolator(), Extrapolator::Nearest);
+        assert!((pdf.xfx_q2(21, 1e-10, 90.0 * 90.0) / value - 1.0).abs() > 1e-6);
+
+        assert_eq!(
+            "logcubic".parse::<Interpolator>()?,
+            Interpolator::LogBicubic
+        );
+        assert_eq!(
+            "spline".parse::<Interpolator>().unwrap_err().to_string(),
+            "unknown interpolator 'spline'"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_alphas() -> Result<()> {
+        init();
+
+        let mut pdf = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
+        let alphas = pdf.alphas();
+
+        assert_eq!(alphas.alphas_type(), AlphaSType::Ipol);
+        assert_eq!(alphas.order_qcd(), 1);
+        assert!((alphas.alphas_q2(90.0 * 90.0)? - pdf.alphas_q2(90.0 * 90.0)).abs() < 1e-14);
+        assert!((alphas.alphas_mz()? - 0.118).abs() < 1e-4);
+
+        let mut ode = AlphaS::ode();
+        ode.set_order_qcd(1);
+        ode.set_alphas_mz(0.120);
+        ode.set_quark_mass(4, 1.51)?;
+        ode.set_quark_mass(5, 4.92)?;
+        ode.set_quark_mass(6, 172.5)?;
+
+        assert_eq!(ode.view().alphas_type(), AlphaSType::Ode);
+        assert!((ode.view().alphas_mz()? - 0.120).abs() < 1e-8);
+        assert_eq!(ode.view().num_flavors_q2(100.0 * 100.0)?, 5);
+        assert!(ode.set_lambda(5, 0.2).is_err());
+
+        pdf.set_alphas(ode);
+
+        assert_eq!(pdf.alphas().alphas_type(), AlphaSType::Ode);
+        assert!((pdf.alphas_q2(91.1876 * 91.1876) - 0.120).abs() < 1e-8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_quark_masses_and_thresholds() -> Result<()> {
+        init();
+
+        let pdf = Pdf::with_setname_and_member("NNPDF31_nlo_as_0118_luxqed", 0)?;
+        let set = pdf.set();
+
+        assert_eq!(pdf.order_qcd()?, 1);
+        let entry = |key| set.entry(key).map(|value| value.parse::<f64>().unwrap());
+
+        assert_eq!(Some(pdf.quark_mass(4)?), entry("MCharm"));
+        assert!((pdf.quark_mass(-5)? - pdf.quark_mass(5)?).abs() < 1e-14);
+        assert_eq!(
+            Some(pdf.quark_threshold(5)?),
+            entry("ThresholdBottom").or_else(|| entry("MBottom"))
+        );
+        assert_eq!(pdf.num_flavors_q2(2.0)?, 3);
+        assert_eq!(pdf.num_flavors_q2(100.0 * 100.0)?, 5);
+
+        assert_eq!(
+            pdf.quark_mass(21).unwrap_err().to_string(),
+            "PDG ID 21 is not a quark"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn quark_masses_missing_from_set() -> Result<()> {
+        init();
+
+        toy_set("ManagedLhapdfMassless")?;
+
+        let pdf = Pdf::with_setname_and_member("ManagedLhapdfMassless", 0)?;
+
+        // `lhapdf.conf` defines all quark masses, but the set doesn't
+        assert_eq!(
+            pdf.quark_mass(4).unwrap_err().to_string(),
+            "PDF set 'ManagedLhapdfMassless' doesn't define 'MCharm'"
+        );
+        assert_eq!(
+            pdf.quark_threshold(-5).unwrap_err().to_string(),
+            "PDF set 'ManagedLhapdfMassless' doesn't define 'ThresholdBottom' or 'MBottom'"
+        );
+        assert_eq!(
+            pdf.order_qcd().unwrap_err().to_string(),
+            "PDF set 'ManagedLhapdfMassless' doesn't define 'OrderQCD'"
+        );
+
+        assert!(unregister_set("ManagedLhapdfMassless")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_tabulated_set() -> Result<()> {
+        init();
+
+        // the set is registered and can be loaded right away
+        toy_set("ManagedLhapdfTabulated")?;
+
+        let pdf = Pdf::with_setname_and_member("ManagedLhapdfTabulated", 0)?;
+
+        assert_eq!(pdf.flavors(), [1, 2, 21]);
+        assert!((pdf.xfx_q2(21, 1e-3, 100.0) / toy_xfx(21, 1e-3, 100.0) - 1.0).abs() < 1e-14);
+        // the function doesn't depend on `q2`, so the interpolation reproduces it between knots
+        assert!((pdf.xfx_q2(2, 0.1, 50.0) / toy_xfx(2, 0.1, 50.0) - 1.0).abs() < 1e-12);
+
+        assert!(unregister_set("ManagedLhapdfTabulated")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_registered_set() -> Result<()> {
+        init();
+
+        let mut writer = toy_set("ManagedLhapdfRegistered")?;
+        writer.add_member_fn(|pid, x, q2| 2.0 * toy_xfx(pid, x, q2))?;
+        writer.set_error_type("replicas");
+        register_set(&writer)?;
+
+        let pdfs = PdfSet::new("ManagedLhapdfRegistered")?.mk_pdfs()?;
+
+        assert_eq!(pdfs.len(), 2);
+        assert!(
+            (pdfs[1].xfx_q2(21, 1e-3, 100.0) / (2.0 * toy_xfx(21, 1e-3, 100.0)) - 1.0).abs()
+                < 1e-14
+        );
+
+        assert!(unregister_set("ManagedLhapdfRegistered")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reload_registered_set() -> Result<()> {
+        init();
+
+        let mut writer = toy_set("ManagedLhapdfReregistered")?;
+        writer.add_member_fn(|pid, x, q2| 2.0 * toy_xfx(pid, x, q2))?;
+        register_set(&writer)?;
+
+        let set = PdfSet::new("ManagedLhapdfReregistered")?;
+        let cached = set.members().collect::<Result<Vec<_>>>()?;
+
+        assert!(Arc::ptr_eq(
+            &cached[1],
+            &Pdf::cached("ManagedLhapdfReregistered", 1)?
+        ));
+
+        // replace the second member and make sure the cache doesn't return the old one
+        let mut writer = toy_set("ManagedLhapdfReregistered")?;
+        writer.add_member_fn(|pid, x, q2| 3.0 * toy_xfx(pid, x, q2))?;
+        register_set(&writer)?;
+
+        let pdf = Pdf::cached("ManagedLhapdfReregistered", 1)?;
+
+        assert!(!Arc::ptr_eq(&cached[1], &pdf));
+        assert!(
+            (pdf.xfx_q2(21, 1e-3, 100.0) / (3.0 * toy_xfx(21, 1e-3, 100.0)) - 1.0).abs() < 1e-14
+        );
+
+        let pdfs = set.members().collect::<Result<Vec<_>>>()?;
+
+        assert!(Arc::ptr_eq(&pdfs[1], &pdf));
+
+        assert!(unregister_set("ManagedLhapdfReregistered")?);
+
+        Ok(())
+    }
+
     #[test]
     fn download_pdf_set() {
+        init();
+
         let _ = Pdf::with_setname_and_member("CT10", 0).unwrap();
     }
 }
@@ -0,0 +1,118 @@
+//! Lockfiles recording which PDF sets a program used, so that a result can be reproduced with
+//! exactly the same PDF data.
+
+use super::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record of the PDF sets that were loaded by a program, usually stored as `pdfs.lock`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lockfile {
+    /// The recorded PDF sets, sorted by name.
+    #[serde(default, rename = "pdfset")]
+    pub sets: Vec<LockedSet>,
+}
+
+/// A single PDF set in a [`Lockfile`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedSet {
+    /// Name of the PDF set.
+    pub name: String,
+    /// The `DataVersion` of the set, if it has one.
+    pub data_version: Option<i32>,
+    /// Directory the set was loaded from.
+    pub path: PathBuf,
+    /// SHA-256 hash of all files in `path`.
+    pub sha256: String,
+    /// The members that were loaded. This is empty if only the metadata was used.
+    pub members: Vec<i32>,
+}
+
+impl Lockfile {
+    /// Read a lockfile from `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the file could not be read or isn't a valid lockfile an error is returned.
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Write this lockfile to `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the file could not be written an error is returned.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, toml::to_string_pretty(self)?)?)
+    }
+
+    /// Check that `set` has the same `DataVersion` and content as the set with the same name in
+    /// this lockfile. The directories and members are allowed to differ.
+    ///
+    /// # Errors
+    ///
+    /// If the set isn't recorded in this lockfile or differs from the recorded one, an error is
+    /// returned.
+    pub fn verify(&self, set: &LockedSet) -> Result<()> {
+        let Some(locked) = self.sets.iter().find(|locked| locked.name == set.name) else {
+            return Err(Error::General(format!(
+                "PDF set '{}' is not recorded in the lockfile",
+                set.name
+            )));
+        };
+
+        if locked.data_version != set.data_version {
+            return Err(Error::General(format!(
+                "PDF set '{}' has DataVersion {:?}, but the lockfile records {:?}",
+                set.name, set.data_version, locked.data_version
+            )));
+        }
+
+        if locked.sha256 != set.sha256 {
+            return Err(Error::General(format!(
+                "content of PDF set '{}' in '{}' doesn't match the lockfile",
+                set.name,
+                set.path.display()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Calculate the SHA-256 hash of the names and contents of all files in `dir`.
+pub fn hash_directory(dir: &Path) -> Result<String> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    // make the hash independent of the order in which the filesystem lists the files
+    files.sort();
+
+    let mut hasher = Sha256::new();
+
+    for file in files.iter().filter(|file| file.is_file()) {
+        let content = fs::read(file)?;
+
+        // UNWRAP: every path returned by `read_dir` has a file name
+        hasher.update(file.file_name().unwrap().as_encoded_bytes());
+        // separate the name from the content and make the boundaries between files unambiguous
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            // UNWRAP: writing into a `String` can't fail
+            write!(hex, "{byte:02x}").unwrap();
+            hex
+        }))
+}
@@ -0,0 +1,174 @@
+//! Parton luminosities.
+//!
+//! The luminosity of the partons `a` and `b` at the hadronic centre-of-mass energy `sqrt(s)` and
+//! the invariant mass `M_X` of the final state is
+//!
+//! ```text
+//! dL/dτ = Σ w ∫_τ^1 dx/x f_a(x, M_X^2) f_b(τ/x, M_X^2)
+//! ```
+//!
+//! with `τ = M_X^2 / s`, where the sum runs over the weighted pairs of partons of a [`Channel`].
+
+use super::backend::PdfBackend;
+use super::integration;
+use super::{Error, Result};
+#[cfg(feature = "lhapdf")]
+use super::{PdfSet, PdfUncertainty};
+
+/// Relative accuracy of the integration.
+const TOLERANCE: f64 = 1e-8;
+
+/// Combination of partons for which a luminosity is calculated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Channel {
+    /// Two gluons.
+    GluonGluon,
+    /// A quark and the antiquark of the same flavour, summed over all quark flavours of the PDF
+    /// and both orderings.
+    QuarkAntiquark,
+    /// A quark or antiquark and a gluon, summed over all quark flavours of the PDF and both
+    /// orderings.
+    QuarkGluon,
+    /// Pairs of PDG IDs with their weights.
+    Custom(Vec<(i32, i32, f64)>),
+}
+
+impl Channel {
+    /// Return the weighted pairs of PDG IDs of this channel, restricted to the quarks defined by
+    /// `pdf`.
+    fn pids<P: PdfBackend + ?Sized>(&self, pdf: &P) -> Vec<(i32, i32, f64)> {
+        let quarks = || pdf.flavors().into_iter().filter(|&pid| pid > 0 && pid <= 6);
+
+        match self {
+            Self::GluonGluon => vec![(21, 21, 1.0)],
+            Self::QuarkAntiquark => quarks()
+                .flat_map(|pid| [(pid, -pid, 1.0), (-pid, pid, 1.0)])
+                .collect(),
+            Self::QuarkGluon => quarks()
+                .flat_map(|pid| {
+                    [
+                        (pid, 21, 1.0),
+                        (21, pid, 1.0),
+                        (-pid, 21, 1.0),
+                        (21, -pid, 1.0),
+                    ]
+                })
+                .collect(),
+            Self::Custom(pids) => pids.clone(),
+        }
+    }
+}
+
+/// Calculate the luminosity `dL/dτ` of `channel` for the hadronic centre-of-mass energy `sqrt_s`
+/// and the invariant mass `mx`, both in `GeV`, at the factorization scale `mx`.
+///
+/// # Errors
+///
+/// If `mx` isn't positive and smaller than `sqrt_s` an error is returned.
+///
+/// # Panics
+///
+/// If `pdf` isn't defined for the momentum fractions between `τ` and `1`, or the scale `mx`, this
+/// function may panic.
+pub fn luminosity<P: PdfBackend + ?Sized>(
+    pdf: &P,
+    channel: &Channel,
+    sqrt_s: f64,
+    mx: f64,
+) -> Result<f64> {
+    // this also catches NaNs
+    if !(mx > 0.0 && mx < sqrt_s) {
+        return Err(Error::General(format!(
+            "the invariant mass {mx} must be positive and smaller than sqrt(s) = {sqrt_s}"
+        )));
+    }
+
+    let tau = (mx / sqrt_s).powi(2);
+    let q2 = mx * mx;
+    let pids = channel.pids(pdf);
+
+    // substitute `x = exp(y)` to flatten the integrand at small `x`
+    let integral = integration::integrate(
+        |y| {
+            let x1 = y.exp();
+            let x2 = tau / x1;
+
+            pids.iter()
+                .map(|&(a, b, weight)| weight * pdf.xfx_q2(a, x1, q2) * pdf.xfx_q2(b, x2, q2))
+                .sum()
+        },
+        tau.ln(),
+        0.0,
+        TOLERANCE,
+    );
+
+    Ok(integral / tau)
+}
+
+/// Calculate the central values and uncertainties of the luminosities of `set`.
+///
+/// The luminosity of `channel` is calculated for every member of `set` and each invariant mass in
+/// `masses`, and the uncertainties are determined for the confidence level `cl` in percent. See
+/// [`luminosity`] for the remaining parameters.
+///
+/// # Errors
+///
+/// If the members of `set` can not be loaded, a mass is invalid or the uncertainties can not be
+/// calculated an error is returned.
+#[cfg(feature = "lhapdf")]
+pub fn uncertainty(
+    set: &PdfSet,
+    channel: &Channel,
+    sqrt_s: f64,
+    masses: &[f64],
+    cl: f64,
+) -> Result<Vec<PdfUncertainty>> {
+    let pdfs = set.mk_pdfs()?;
+
+    masses
+        .iter()
+        .map(|&mx| {
+            let values = pdfs
+                .iter()
+                .map(|pdf| luminosity(pdf, channel, sqrt_s, mx))
+                .collect::<Result<Vec<_>>>()?;
+
+            set.uncertainty(&values, cl, false)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::backend::Toy;
+    use super::*;
+
+    #[test]
+    fn channel_luminosities() {
+        // `x f(x) = x (1 - x)` for every flavour
+        let toy = Toy::new(vec![-2, -1, 1, 2, 21], 1e-9, |_, x, _| x * (1.0 - x));
+        let sqrt_s = 13_000.0;
+
+        for mx in [10.0, 125.0, 1000.0, 6000.0] {
+            let tau: f64 = (mx / sqrt_s) * (mx / sqrt_s);
+            let expected = (-(1.0 + tau)).mul_add(tau.ln(), -2.0 * (1.0 - tau));
+            let gg = luminosity(&toy, &Channel::GluonGluon, sqrt_s, mx).unwrap();
+
+            assert!((gg / expected - 1.0).abs() < 1e-7, "{gg} != {expected}");
+
+            // two quark flavours in two orderings
+            let qqbar = luminosity(&toy, &Channel::QuarkAntiquark, sqrt_s, mx).unwrap();
+            assert!((qqbar / gg - 4.0).abs() < 1e-10);
+
+            let qg = luminosity(&toy, &Channel::QuarkGluon, sqrt_s, mx).unwrap();
+            assert!((qg / gg - 8.0).abs() < 1e-10);
+
+            let custom = Channel::Custom(vec![(1, 21, 0.5), (2, -2, 1.5)]);
+            let custom = luminosity(&toy, &custom, sqrt_s, mx).unwrap();
+            assert!((custom / gg - 2.0).abs() < 1e-10);
+        }
+
+        assert!(luminosity(&toy, &Channel::GluonGluon, sqrt_s, sqrt_s).is_err());
+        assert!(luminosity(&toy, &Channel::GluonGluon, sqrt_s, 0.0).is_err());
+    }
+}
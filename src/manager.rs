@@ -3,22 +3,34 @@
 //! directory specified in our configuration file.
 
 use super::ffi::{self, PDF, PDFSet};
+use super::interpolation::{Extrapolator, Interpolator};
+use super::lockfile::{self, LockedSet, Lockfile};
+use super::memory;
 use super::unmanaged;
 use super::{Error, Result};
 use cxx::UniquePtr;
 use flate2::read::GzDecoder;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io::{self, ErrorKind, Write};
+use std::iter;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
 use tar::Archive;
 use url::Url;
 
-const LHAPDF_CONFIG: &str = "Verbosity: 1
+/// First line of the `lhapdf.conf` files written by this crate. Only files starting with it are
+/// rewritten when the settings change, so that changes made by hand are never overwritten.
+const LHAPDF_CONF_MARKER: &str = "# written by managed-lhapdf from the [lhapdf] table of \
+                                  managed-lhapdf.toml; remove this line to edit this file by hand";
+
+/// Contents of `lhapdf.conf` written by versions of this crate that didn't add
+/// [`LHAPDF_CONF_MARKER`].
+const UNMARKED_LHAPDF_CONF: &str = "Verbosity: 1
 Interpolator: logcubic
 Extrapolator: continuation
 ForcePositive: 0
@@ -33,14 +45,202 @@ MTop: 172.9
 Pythia6LambdaV5Compat: true
 ";
 
+/// Global settings of LHAPDF, which are written to `lhapdf.conf`. The keys are the same as the
+/// ones in `lhapdf.conf`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LhapdfSettings {
+    #[serde(rename = "Verbosity")]
+    verbosity: i32,
+    #[serde(rename = "Interpolator")]
+    interpolator: Interpolator,
+    #[serde(rename = "Extrapolator")]
+    extrapolator: Extrapolator,
+    #[serde(rename = "ForcePositive")]
+    force_positive: i32,
+    #[serde(rename = "AlphaS_Type")]
+    alphas_type: String,
+    #[serde(rename = "MZ")]
+    mz: f64,
+    #[serde(rename = "MUp")]
+    mup: f64,
+    #[serde(rename = "MDown")]
+    mdown: f64,
+    #[serde(rename = "MStrange")]
+    mstrange: f64,
+    #[serde(rename = "MCharm")]
+    mcharm: f64,
+    #[serde(rename = "MBottom")]
+    mbottom: f64,
+    #[serde(rename = "MTop")]
+    mtop: f64,
+    #[serde(rename = "Pythia6LambdaV5Compat")]
+    pythia6_lambda_v5_compat: bool,
+}
+
+impl Default for LhapdfSettings {
+    fn default() -> Self {
+        Self {
+            verbosity: 1,
+            interpolator: Interpolator::LogBicubic,
+            extrapolator: Extrapolator::Continuation,
+            force_positive: 0,
+            alphas_type: "analytic".to_owned(),
+            mz: 91.1876,
+            mup: 0.002,
+            mdown: 0.005,
+            mstrange: 0.10,
+            mcharm: 1.29,
+            mbottom: 4.19,
+            mtop: 172.9,
+            pythia6_lambda_v5_compat: true,
+        }
+    }
+}
+
+impl LhapdfSettings {
+    /// Return the default verbosity level.
+    #[must_use]
+    pub const fn verbosity(&self) -> i32 {
+        self.verbosity
+    }
+
+    /// Return the default interpolator.
+    #[must_use]
+    pub const fn interpolator(&self) -> Interpolator {
+        self.interpolator
+    }
+
+    /// Return the default extrapolator.
+    #[must_use]
+    pub const fn extrapolator(&self) -> Extrapolator {
+        self.extrapolator
+    }
+
+    /// Return the default mode that forces PDFs to be positive, see [`Pdf::force_positive`].
+    ///
+    /// [`Pdf::force_positive`]: crate::Pdf::force_positive
+    #[must_use]
+    pub const fn force_positive(&self) -> i32 {
+        self.force_positive
+    }
+
+    /// Return the default type of the strong coupling.
+    #[must_use]
+    pub fn alphas_type(&self) -> &str {
+        &self.alphas_type
+    }
+
+    /// Return the mass of the Z boson.
+    #[must_use]
+    pub const fn mz(&self) -> f64 {
+        self.mz
+    }
+
+    /// Return the default mass of the quark with PDG ID `pid`, or `None` if `pid` isn't a quark.
+    /// Antiquarks have the same masses as their quarks.
+    #[must_use]
+    pub const fn quark_mass(&self, pid: i32) -> Option<f64> {
+        match pid.abs() {
+            1 => Some(self.mdown),
+            2 => Some(self.mup),
+            3 => Some(self.mstrange),
+            4 => Some(self.mcharm),
+            5 => Some(self.mbottom),
+            6 => Some(self.mtop),
+            _ => None,
+        }
+    }
+
+    /// Return whether Pythia 6's definition of Lambda in the strong coupling is used.
+    #[must_use]
+    pub const fn pythia6_lambda_v5_compat(&self) -> bool {
+        self.pythia6_lambda_v5_compat
+    }
+
+    /// Return the contents of `lhapdf.conf` for these settings.
+    fn to_lhapdf_conf(&self) -> String {
+        format!(
+            "{LHAPDF_CONF_MARKER}
+Verbosity: {}
+Interpolator: {}
+Extrapolator: {}
+ForcePositive: {}
+AlphaS_Type: {}
+MZ: {}
+MUp: {}
+MDown: {}
+MStrange: {}
+MCharm: {}
+MBottom: {}
+MTop: {}
+Pythia6LambdaV5Compat: {}
+",
+            self.verbosity,
+            self.interpolator,
+            self.extrapolator,
+            self.force_positive,
+            self.alphas_type,
+            self.mz,
+            self.mup,
+            self.mdown,
+            self.mstrange,
+            self.mcharm,
+            self.mbottom,
+            self.mtop,
+            self.pythia6_lambda_v5_compat
+        )
+    }
+}
+
 /// Configuration for this library.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     lhapdf_data_path_read: Vec<PathBuf>,
     lhapdf_data_path_write: PathBuf,
-    pdfsets_index_url: Url,
+    #[serde(alias = "pdfsets_index_url", deserialize_with = "one_or_many")]
+    pdfsets_index_urls: Vec<Url>,
+    #[serde(default = "default_index_max_age")]
+    index_max_age: u64,
     pdfset_urls: Vec<Url>,
+    #[serde(default)]
+    pinned_versions: BTreeMap<String, i32>,
+    #[serde(default)]
+    lhapdf: LhapdfSettings,
+}
+
+// older configuration files specify a single URL
+fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<Url>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Url),
+        Many(Vec<Url>),
+    }
+
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(url) => Ok(vec![url]),
+        OneOrMany::Many(urls) if urls.is_empty() => Err(serde::de::Error::invalid_length(
+            0,
+            &"at least one URL of `pdfsets.index`",
+        )),
+        OneOrMany::Many(urls) => Ok(urls),
+    }
+}
+
+const fn default_index_max_age() -> u64 {
+    // one week
+    7 * 24 * 60 * 60
+}
+
+/// Validators of the local copy of `pdfsets.index`, used to make conditional requests.
+#[derive(Default, Deserialize, Serialize)]
+struct IndexMetadata {
+    etag: Option<String>,
+    last_modified: Option<String>,
 }
 
 impl Default for Config {
@@ -53,10 +253,14 @@ impl Default for Config {
                 .unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| env::temp_dir()))
                 .join("managed-lhapdf"),
             // UNWRAP: a panic means the static string is malformed
-            pdfsets_index_url: Url::parse("https://lhapdfsets.web.cern.ch/current/pdfsets.index")
-                .unwrap(),
+            pdfsets_index_urls: vec![
+                Url::parse("https://lhapdfsets.web.cern.ch/current/pdfsets.index").unwrap(),
+            ],
+            index_max_age: default_index_max_age(),
             // UNWRAP: a panic means the static string is malformed
             pdfset_urls: vec![Url::parse("https://lhapdfsets.web.cern.ch/current/").unwrap()],
+            pinned_versions: BTreeMap::new(),
+            lhapdf: LhapdfSettings::default(),
         };
 
         // if there's an environment variable that the user set use its value
@@ -75,21 +279,430 @@ impl Default for Config {
     }
 }
 
+fn call(request: ureq::Request) -> Result<ureq::Response> {
+    request.call().map_err(|err| match err {
+        // we need to catch 404 errors so we can potentially retry
+        ureq::Error::Status(404, _) => Error::Http404,
+        err @ _ => Error::Other(anyhow::Error::new(err)),
+    })
+}
+
 fn get_url(url: &Url) -> Result<Box<dyn std::io::Read + Send + Sync + 'static>> {
-    ureq::request_url("GET", url)
-        .call()
-        .map_err(|err| match err {
-            // we need to catch 404 errors so we can potentially retry
-            ureq::Error::Status(404, _) => Error::Http404,
-            err @ _ => Error::Other(anyhow::Error::new(err)),
+    call(ureq::request_url("GET", url)).map(ureq::Response::into_reader)
+}
+
+/// Return the path of the local copy of the `pdfsets.index` source at `url`. For `file://` URLs
+/// this is the file itself.
+fn index_source_path(lhapdf_data_path_write: &Path, url: &Url) -> Result<PathBuf> {
+    if url.scheme() == "file" {
+        return url
+            .to_file_path()
+            .map_err(|()| Error::General(format!("'{url}' is not a valid file path")));
+    }
+
+    let name: String = url
+        .as_str()
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '.' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    Ok(lhapdf_data_path_write
+        .join("pdfsets.index.d")
+        .join(format!("{name}.index")))
+}
+
+/// Download the `pdfsets.index` source at `url` into `path`, unless the server tells us that our
+/// copy is still current.
+fn refresh_index_source(url: &Url, path: &Path) -> Result<()> {
+    let metadata_path = path.with_extension("toml");
+
+    // the validators are only meaningful if the file they describe exists
+    let metadata: IndexMetadata = if path.exists() {
+        fs::read_to_string(&metadata_path)
+            .ok()
+            .and_then(|string| toml::from_str(&string).ok())
+            .unwrap_or_default()
+    } else {
+        IndexMetadata::default()
+    };
+
+    let mut request = ureq::request_url("GET", url);
+
+    if let Some(etag) = &metadata.etag {
+        request = request.set("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &metadata.last_modified {
+        request = request.set("If-Modified-Since", last_modified);
+    }
+
+    let response = call(request)?;
+
+    // our copy is up-to-date
+    if response.status() == 304 {
+        return Ok(());
+    }
+
+    let metadata = IndexMetadata {
+        etag: response.header("ETag").map(ToOwned::to_owned),
+        last_modified: response.header("Last-Modified").map(ToOwned::to_owned),
+    };
+
+    let download = path.with_extension(format!("{}", std::process::id()));
+    io::copy(&mut response.into_reader(), &mut File::create(&download)?)?;
+    fs::rename(download, path)?;
+    fs::write(metadata_path, toml::to_string_pretty(&metadata)?)?;
+
+    Ok(())
+}
+
+/// Merge the contents of `pdfsets.index` sources into the contents of a single index. Sources
+/// listed first take precedence.
+///
+/// LHAPDF assigns a PDF set all LHAIDs from its first one up to the first LHAID of the next set in
+/// the index. A set therefore conflicts with every set of another source whose first LHAID falls
+/// into its range, where the range of each set is the one given by its own source and the last set
+/// of a source is assumed to have a single LHAID. Entries that agree with an entry used before are
+/// skipped without comparing their ranges, so that mirrors that don't list every set don't produce
+/// conflicts.
+fn merge_index_sources(sources: &[(&Url, String)]) -> (String, Vec<IndexConflict>) {
+    // the entries `(LHAID, SETNAME, LINE)` of every source, sorted by LHAID
+    let sources: Vec<(&Url, Vec<_>)> = sources
+        .iter()
+        .map(|(url, content)| {
+            let mut entries: Vec<_> = content
+                .lines()
+                .filter_map(|line| {
+                    let mut columns = line.split_whitespace();
+
+                    // each line has the format `<LHAID> <SETNAME> <VERSION>`; skip everything else
+                    let lhaid: i32 = columns.next()?.parse().ok()?;
+                    let setname = columns.next()?;
+
+                    Some((lhaid, setname, line))
+                })
+                .collect();
+            entries.sort_by_key(|&(lhaid, _, _)| lhaid);
+
+            (*url, entries)
+        })
+        .collect();
+
+    // the entries used so far, with their end and the source they were taken from
+    let mut used: BTreeMap<i32, (&str, &str, i32, &Url)> = BTreeMap::new();
+    let mut conflicts = Vec::new();
+
+    for &(url, ref entries) in &sources {
+        for (index, &(lhaid, setname, line)) in entries.iter().enumerate() {
+            let end = entries
+                .get(index + 1)
+                .map_or_else(|| lhaid.saturating_add(1), |&(next, _, _)| next);
+
+            // check whether `lhaid` is already assigned to a set
+            if let Some((&start, &(used_setname, _, used_end, used_source))) =
+                used.range(..=lhaid).next_back()
+                && (lhaid < used_end)
+            {
+                // mirrors often share entries, which is fine as long as they agree
+                if (start != lhaid) || (used_setname != setname) {
+                    conflicts.push(IndexConflict {
+                        lhaid,
+                        setname: used_setname.to_owned(),
+                        source: used_source.clone(),
+                        ignored_setname: setname.to_owned(),
+                        ignored_source: url.clone(),
+                    });
+                }
+
+                continue;
+            }
+
+            // the sets used so far take the upper LHAIDs of this set's range
+            for (&start, &(used_setname, _, _, used_source)) in used.range(lhaid..end) {
+                conflicts.push(IndexConflict {
+                    lhaid: start,
+                    setname: used_setname.to_owned(),
+                    source: used_source.clone(),
+                    ignored_setname: setname.to_owned(),
+                    ignored_source: url.clone(),
+                });
+            }
+
+            used.insert(lhaid, (setname, line, end, url));
+        }
+    }
+
+    let merged = used
+        .into_values()
+        .map(|(_, line, _, _)| line.to_owned() + "\n")
+        .collect();
+
+    (merged, conflicts)
+}
+
+/// Merge the local copies of all `pdfsets.index` sources into the contents of a single index.
+/// Sources without a local copy, for instance because they couldn't be downloaded, are skipped.
+fn merge_pdfsets_indices(
+    lhapdf_data_path_write: &Path,
+    config: &Config,
+) -> Result<(String, Vec<IndexConflict>)> {
+    let mut sources = Vec::new();
+
+    for url in config.pdfsets_index_urls() {
+        match fs::read_to_string(index_source_path(lhapdf_data_path_write, url)?) {
+            Ok(content) => sources.push((url, content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(merge_index_sources(&sources))
+}
+
+/// Refresh all sources of `pdfsets.index` and merge them into the single file that LHAPDF reads.
+/// Returns `true` if the merged file was changed, and the conflicts between the sources.
+fn refresh_pdfsets_index(
+    lhapdf_data_path_write: &Path,
+    config: &Config,
+) -> Result<(bool, Vec<IndexConflict>)> {
+    fs::create_dir_all(lhapdf_data_path_write.join("pdfsets.index.d"))?;
+
+    let mut available = false;
+    let mut error = None;
+
+    for url in config.pdfsets_index_urls() {
+        let path = index_source_path(lhapdf_data_path_write, url)?;
+
+        // local files are always up-to-date
+        if url.scheme() != "file"
+            && let Err(err) = refresh_index_source(url, &path)
+        {
+            error.get_or_insert(err);
+        }
+
+        // keep using an older copy if there is one, and skip the source otherwise
+        available |= path.exists();
+    }
+
+    // fail only if there's nothing to merge
+    if !available {
+        return Err(error.unwrap_or_else(|| {
+            Error::General("none of the sources of `pdfsets.index` is available".to_owned())
+        }));
+    }
+
+    let (merged, conflicts) = merge_pdfsets_indices(lhapdf_data_path_write, config)?;
+    let pdfsets_index = lhapdf_data_path_write.join("pdfsets.index");
+
+    if fs::read_to_string(&pdfsets_index).is_ok_and(|content| content == merged) {
+        // nothing changed; restart the clock for `index_max_age`
+        File::options()
+            .write(true)
+            .open(&pdfsets_index)?
+            .set_modified(SystemTime::now())?;
+
+        return Ok((false, conflicts));
+    }
+
+    // write into a temporary file first so that LHAPDF never reads a partially written index
+    let tmp = lhapdf_data_path_write.join(format!("pdfsets.index.{}", std::process::id()));
+    fs::write(&tmp, merged)?;
+    fs::rename(tmp, pdfsets_index)?;
+
+    Ok((true, conflicts))
+}
+
+/// Read the `DataVersion` entry from the `.info` file at `path`.
+fn data_version(path: &Path) -> Result<Option<i32>> {
+    for line in fs::read_to_string(path)?.lines() {
+        if let Some(value) = line.strip_prefix("DataVersion:") {
+            return value.trim().parse().map(Some).map_err(|err| {
+                Error::General(format!(
+                    "problem while parsing DataVersion in '{}': '{err}'",
+                    path.display()
+                ))
+            });
+        }
+    }
+
+    Ok(None)
+}
+
+/// Return an error if the PDF set `name` is pinned to a different `DataVersion` than the one in
+/// its `.info` file `info`, which was taken from `origin`.
+fn check_pinned_version(name: &str, info: &Path, origin: &str, config: &Config) -> Result<()> {
+    if let Some(&pinned) = config.pinned_versions().get(name) {
+        let version = data_version(info)?;
+
+        if version != Some(pinned) {
+            return Err(Error::General(format!(
+                "PDF set '{name}' is pinned to DataVersion {pinned}, but {origin} has {}",
+                version.map_or_else(|| "none".to_owned(), |version| version.to_string())
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the versions of all PDF sets listed in `pdfsets.index`.
+fn index_versions(pdfsets_index: &Path) -> Result<BTreeMap<String, i32>> {
+    Ok(fs::read_to_string(pdfsets_index)?
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace().skip(1);
+            let setname = columns.next()?;
+            let version = columns.next()?.parse().ok()?;
+
+            Some((setname.to_owned(), version))
         })
-        .map(ureq::Response::into_reader)
+        .collect())
 }
 
-struct LhapdfData;
+/// Download the PDF set `name` into a temporary directory and move it into
+/// `lhapdf_data_path_write` once it's complete, replacing an existing copy. Returns `false` if
+/// none of the URLs has the set.
+fn fetch_set(lhapdf_data_path_write: &Path, name: &str, config: &Config) -> Result<bool> {
+    for url in config.pdfset_urls() {
+        let response = get_url(&url.join(&format!("{name}.tar.gz"))?);
+
+        // if the URL didn't have the PDF set, try the next one
+        if let Err(Error::Http404) = response {
+            continue;
+        }
+
+        let download = lhapdf_data_path_write.join(format!(".{name}.{}", std::process::id()));
+        let old = download.join(format!("{name}.old"));
+        fs::create_dir_all(&download)?;
+
+        let result = Archive::new(GzDecoder::new(response?))
+            .unpack(&download)
+            .map_err(Error::from)
+            .and_then(|()| {
+                let info = download.join(name).join(format!("{name}.info"));
+
+                if !info.is_file() {
+                    return Err(Error::General(format!(
+                        "archive of PDF set '{name}' from '{url}' doesn't contain '{name}/{name}.info'"
+                    )));
+                }
+
+                check_pinned_version(name, &info, &format!("'{url}'"), config)?;
+
+                let target = lhapdf_data_path_write.join(name);
+
+                // renaming is atomic, so LHAPDF never sees a partially unpacked set. Between the
+                // two renames, however, the set doesn't exist; loading it in this process waits
+                // for us, but other processes may fail to find it
+                if target.exists() {
+                    fs::rename(&target, &old)?;
+                }
+
+                if let Err(err) = fs::rename(download.join(name), &target) {
+                    // put the previous version back, otherwise it's removed with `download`
+                    if old.exists() {
+                        fs::rename(&old, &target).map_err(|restore_err| {
+                            Error::General(format!(
+                                "could not install PDF set '{name}': '{err}', and could not restore \
+                                 its previous version from '{}': '{restore_err}'",
+                                old.display()
+                            ))
+                        })?;
+                    }
+
+                    return Err(err.into());
+                }
+
+                Ok(())
+            });
+
+        // remove what's left over, including the previous version of the set - unless it couldn't
+        // be restored
+        if !old.exists() || result.is_ok() {
+            fs::remove_dir_all(&download)?;
+        }
+
+        return result.map(|()| true);
+    }
+
+    Ok(false)
+}
+
+/// A locally installed PDF set for which a newer version is available.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetUpdate {
+    /// Name of the PDF set.
+    pub name: String,
+    /// `DataVersion` of the installed set, if it has one.
+    pub local_version: Option<i32>,
+    /// Version of the set listed in `pdfsets.index`.
+    pub remote_version: i32,
+}
+
+/// A PDF set found in one of the local data directories.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstalledSet {
+    /// Name of the PDF set.
+    pub name: String,
+    /// Directory containing the files of the PDF set.
+    pub path: PathBuf,
+    /// `DataVersion` of the set, if it has one.
+    pub data_version: Option<i32>,
+}
+
+/// A PDF set listed in `pdfsets.index`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoteSet {
+    /// Name of the PDF set.
+    pub name: String,
+    /// LHAID of the first member of the set.
+    pub lhaid: i32,
+    /// `DataVersion` of the set, if the index lists it.
+    pub data_version: Option<i32>,
+}
+
+/// Conflict between two sources of `pdfsets.index` that map the same LHAID to different PDF sets.
+///
+/// LHAPDF assigns a PDF set the LHAIDs from its first one up to the first LHAID of the next set,
+/// so two sets conflict if they have the same first LHAID, or if the first LHAID of one of them
+/// falls into the range of the other.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexConflict {
+    /// The first LHAID both sources assign to different PDF sets.
+    pub lhaid: i32,
+    /// Name of the PDF set that is used for `lhaid`.
+    pub setname: String,
+    /// The source that `setname` was taken from.
+    pub source: Url,
+    /// Name of the PDF set that was ignored.
+    pub ignored_setname: String,
+    /// The source that `ignored_setname` was taken from.
+    pub ignored_source: Url,
+}
+
+struct LhapdfData {
+    // LHAIDs that weren't found even after refreshing `pdfsets.index`
+    missing_lhaids: BTreeSet<i32>,
+    // PDF sets and their members that were loaded so far
+    loaded_sets: BTreeMap<String, BTreeSet<i32>>,
+    // the lockfile that loaded PDF sets are verified against and the sets that passed
+    verification: Option<(Lockfile, BTreeSet<String>)>,
+}
 
 impl Config {
-    /// Return the only instance of this type.
+    /// Return the only instance of this type. On first use the configuration file is read, or
+    /// created with default values if it doesn't exist, and `lhapdf.conf` and `pdfsets.index` are
+    /// set up.
+    ///
+    /// # Panics
+    ///
+    /// If the configuration file could not be read or written, or the data directory could not be
+    /// set up this function panics.
     pub fn get() -> &'static Self {
         static SINGLETON: OnceLock<Result<Config>> = OnceLock::new();
 
@@ -131,46 +744,54 @@ impl Config {
                 // create download directory for `lhapdf.conf`
                 fs::create_dir_all(lhapdf_data_path_write)?;
 
-                // MSRV 1.77.0: use `File::create_new` instead
-                if let Ok(mut file) = File::options()
-                    .read(true)
-                    .write(true)
-                    .create_new(true)
-                    .open(lhapdf_data_path_write.join("lhapdf.conf"))
-                {
-                    // if `lhapdf.conf` doesn't exist, create it
-                    file.write_all(LHAPDF_CONFIG.as_bytes())?;
+                let lhapdf_conf = lhapdf_data_path_write.join("lhapdf.conf");
+                let settings = config.lhapdf().to_lhapdf_conf();
+
+                // (re)write `lhapdf.conf` if it doesn't reflect our settings, unless it was written
+                // by someone else
+                let rewrite = match fs::read_to_string(&lhapdf_conf) {
+                    Ok(content) => {
+                        (content != settings)
+                            && (content.starts_with(LHAPDF_CONF_MARKER)
+                                || content == UNMARKED_LHAPDF_CONF)
+                    }
+                    Err(err) if err.kind() == ErrorKind::NotFound => true,
+                    Err(err) => Err(err)?,
+                };
+
+                if rewrite {
+                    // write to a temporary file first so LHAPDF never reads a partial file
+                    let tmp = lhapdf_conf.with_extension(format!("{}", std::process::id()));
+                    fs::write(&tmp, settings)?;
+                    fs::rename(tmp, lhapdf_conf)?;
                 }
 
-                let pdfsets_index = lhapdf_data_path_write.join("pdfsets.index");
+                let lock_file = File::create(lhapdf_data_path_write.join("pdfsets.lock"))?;
+                lock_file.lock()?;
+
+                let stale = fs::metadata(lhapdf_data_path_write.join("pdfsets.index"))
+                    .and_then(|metadata| metadata.modified())
+                    .map(|modified| {
+                        // a modification time in the future doesn't make the file stale
+                        modified.elapsed().unwrap_or_default() > config.index_max_age()
+                    });
 
-                // MSRV 1.77.0: use `File::create_new` instead
-                if let Ok(mut file) = File::options()
-                    .read(true)
-                    .write(true)
-                    .create_new(true)
-                    .open(pdfsets_index)
-                {
+                // conflicts between the sources are reported by `pdfsets_index_conflicts`
+                match stale {
                     // if `pdfsets.index` doesn't exist, download it
-                    let mut reader = get_url(config.pdfsets_index_url())?;
-                    io::copy(&mut reader, &mut file)?;
+                    Err(_) => {
+                        refresh_pdfsets_index(lhapdf_data_path_write, &config)?;
+                    }
+                    // if it's too old try to refresh it, but keep using the old copy if that
+                    // fails, for instance because we're offline
+                    Ok(true) => {
+                        let _ = refresh_pdfsets_index(lhapdf_data_path_write, &config);
+                    }
+                    Ok(false) => {}
                 }
-            }
 
-            // we use the environment variable `LHAPDF_DATA_PATH` to let LHAPDF know where we've
-            // stored our PDFs
-
-            let lhapdf_data_path = config
-                .lhapdf_data_path_write()
-                .into_iter()
-                .chain(config.lhapdf_data_path_read.iter().map(Deref::deref))
-                .map(|path| path.as_os_str())
-                .collect::<Vec<_>>()
-                .join(&OsString::from(":"));
-            // as long as `static Config _cfg` in LHAPDF's `src/Config.cc` is `static` and not
-            // `thread_local`, this belongs here; otherwise move it out of the singleton
-            // initialization
-            unsafe { env::set_var("LHAPDF_DATA_PATH", lhapdf_data_path) };
+                lock_file.unlock()?;
+            }
 
             Ok(config)
         });
@@ -181,6 +802,7 @@ impl Config {
     }
 
     /// Return the path where `managed-lhapdf` will download PDF sets and `pdfsets.index` to.
+    #[must_use]
     pub fn lhapdf_data_path_write(&self) -> Option<&Path> {
         if self.lhapdf_data_path_write.as_os_str().is_empty() {
             None
@@ -189,16 +811,51 @@ impl Config {
         }
     }
 
-    /// Return the URL where the file `pdfsets.index` will downloaded from.
+    /// Return the URL of the first file that is merged into `pdfsets.index`.
+    #[deprecated(since = "0.5.0", note = "use `pdfsets_index_urls` instead")]
+    #[must_use]
     pub fn pdfsets_index_url(&self) -> &Url {
-        &self.pdfsets_index_url
+        // UNWRAP: the configuration file must give at least one URL
+        self.pdfsets_index_urls
+            .first()
+            .unwrap_or_else(|| unreachable!())
+    }
+
+    /// Return the URLs of the files that are merged into `pdfsets.index`, in order of precedence.
+    /// Local files are given as `file://` URLs.
+    #[must_use]
+    pub fn pdfsets_index_urls(&self) -> &[Url] {
+        &self.pdfsets_index_urls
+    }
+
+    /// Return the age after which the local copy of `pdfsets.index` is considered outdated and
+    /// checked for updates.
+    #[must_use]
+    pub const fn index_max_age(&self) -> Duration {
+        Duration::from_secs(self.index_max_age)
     }
 
     /// Return the URLs that should be searched for PDF sets, if they are not available in the
     /// local cache.
+    #[must_use]
     pub fn pdfset_urls(&self) -> &[Url] {
         &self.pdfset_urls
     }
+
+    /// Return the global LHAPDF settings that are written to `lhapdf.conf`. If `lhapdf.conf` was
+    /// edited by hand, it is never overwritten and LHAPDF uses its settings instead.
+    #[must_use]
+    pub const fn lhapdf(&self) -> &LhapdfSettings {
+        &self.lhapdf
+    }
+
+    /// Return the `DataVersion`s that PDF sets are pinned to. Downloading or updating a pinned set
+    /// fails if the server has a different version, and loading it fails if the installed copy has
+    /// a different version.
+    #[must_use]
+    pub const fn pinned_versions(&self) -> &BTreeMap<String, i32> {
+        &self.pinned_versions
+    }
 }
 
 impl From<toml::ser::Error> for Error {
@@ -221,7 +878,11 @@ impl From<url::ParseError> for Error {
 
 impl LhapdfData {
     fn get() -> &'static Mutex<Self> {
-        static SINGLETON: Mutex<LhapdfData> = Mutex::new(LhapdfData);
+        static SINGLETON: Mutex<LhapdfData> = Mutex::new(LhapdfData {
+            missing_lhaids: BTreeSet::new(),
+            loaded_sets: BTreeMap::new(),
+            verification: None,
+        });
         &SINGLETON
     }
 
@@ -230,45 +891,298 @@ impl LhapdfData {
             let lock_file = File::create(lhapdf_data_path_write.join(format!("{name}.lock")))?;
             lock_file.lock()?;
 
-            for url in config.pdfset_urls() {
-                let response = get_url(&url.join(&format!("{name}.tar.gz"))?);
+            // if we didn't find the PDF set, it's LHAPDF's turn to report the error
+            fetch_set(lhapdf_data_path_write, name, config)?;
+
+            lock_file.unlock()?;
+        }
+
+        Ok(())
+    }
+
+    fn update_set(&self, name: &str, config: &Config) -> Result<()> {
+        let Some(lhapdf_data_path_write) = config.lhapdf_data_path_write() else {
+            return Err(Error::General(
+                "can not update PDF sets without `lhapdf_data_path_write`".to_owned(),
+            ));
+        };
+
+        let lock_file = File::create(lhapdf_data_path_write.join(format!("{name}.lock")))?;
+        lock_file.lock()?;
+
+        if !fetch_set(lhapdf_data_path_write, name, config)? {
+            return Err(Error::General(format!(
+                "PDF set '{name}' was not found at any of the configured URLs"
+            )));
+        }
+
+        lock_file.unlock()?;
+
+        Ok(())
+    }
+
+    fn check_updates(&self, config: &Config) -> Result<Vec<SetUpdate>> {
+        let Some(lhapdf_data_path_write) = config.lhapdf_data_path_write() else {
+            return Ok(Vec::new());
+        };
+
+        // make sure we compare against the latest versions
+        self.update_pdfsets_index(config)?;
 
-                // if the URL didn't have the PDF set, try the next one
-                if let Err(Error::Http404) = response {
+        let remote_versions = index_versions(&lhapdf_data_path_write.join("pdfsets.index"))?;
+        let mut updates = Vec::new();
+
+        for entry in fs::read_dir(lhapdf_data_path_write)? {
+            let Ok(name) = entry?.file_name().into_string() else {
+                continue;
+            };
+            let info = lhapdf_data_path_write
+                .join(&name)
+                .join(format!("{name}.info"));
+
+            // skip everything that isn't a PDF set, and sets whose version the user has chosen
+            if !info.is_file() || config.pinned_versions().contains_key(&name) {
+                continue;
+            }
+
+            let Some(&remote_version) = remote_versions.get(&name) else {
+                continue;
+            };
+            let local_version = data_version(&info)?;
+
+            if local_version.is_none_or(|version| version < remote_version) {
+                updates.push(SetUpdate {
+                    name,
+                    local_version,
+                    remote_version,
+                });
+            }
+        }
+
+        updates.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+        Ok(updates)
+    }
+
+    fn installed_sets(&self, config: &Config) -> Result<Vec<InstalledSet>> {
+        let mut sets = BTreeMap::new();
+        let mut shadowed = BTreeSet::new();
+
+        // sets in directories listed first shadow sets with the same name in later ones
+        for dir in config
+            .lhapdf_data_path_write()
+            .into_iter()
+            .chain(config.lhapdf_data_path_read.iter().map(Deref::deref))
+        {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+
+            for entry in entries {
+                let Ok(name) = entry?.file_name().into_string() else {
                     continue;
-                }
+                };
+                let path = dir.join(&name);
+                let info = path.join(format!("{name}.info"));
 
-                Archive::new(GzDecoder::new(response?)).unpack(lhapdf_data_path_write)?;
+                if !info.is_file() || !shadowed.insert(name.clone()) {
+                    continue;
+                }
 
-                // we found a PDF set, now it's LHAPDF's turn
-                break;
+                // skip sets with unreadable metadata, but let them shadow sets in later directories,
+                // because that's what LHAPDF does
+                let Ok(data_version) = data_version(&info) else {
+                    continue;
+                };
+                sets.insert(
+                    name.clone(),
+                    InstalledSet {
+                        name,
+                        path,
+                        data_version,
+                    },
+                );
             }
+        }
 
-            lock_file.unlock()?;
+        Ok(sets.into_values().collect())
+    }
+
+    fn remote_sets(&self, config: &Config) -> Result<Vec<RemoteSet>> {
+        let Some(lhapdf_data_path_write) = config.lhapdf_data_path_write() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(
+            fs::read_to_string(lhapdf_data_path_write.join("pdfsets.index"))?
+                .lines()
+                .filter_map(|line| {
+                    let mut columns = line.split_whitespace();
+                    let lhaid: i32 = columns.next()?.parse().ok()?;
+                    let name = columns.next()?.to_owned();
+                    let data_version = columns.next().and_then(|version| version.parse().ok());
+
+                    Some(RemoteSet {
+                        name,
+                        lhaid,
+                        data_version,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn install_set(&self, name: &str, config: &Config) -> Result<PathBuf> {
+        let path = self.pdfset_path(name).or_else(|_| {
+            self.download_set(name, config)
+                .and_then(|()| self.pdfset_path(name))
+        })?;
+
+        self.check_installed_version(name, config).map(|()| path)
+    }
+
+    fn remove_set(&self, name: &str, config: &Config) -> Result<bool> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(Error::General(format!(
+                "'{name}' is not a valid PDF set name"
+            )));
         }
 
-        Ok(())
+        // we never remove anything from the directories we only read from
+        let Some(lhapdf_data_path_write) = config.lhapdf_data_path_write() else {
+            return Ok(false);
+        };
+        let path = lhapdf_data_path_write.join(name);
+
+        if !path.join(format!("{name}.info")).is_file() {
+            return Ok(false);
+        }
+
+        let lock_file = File::create(lhapdf_data_path_write.join(format!("{name}.lock")))?;
+        lock_file.lock()?;
+
+        fs::remove_dir_all(path)?;
+
+        lock_file.unlock()?;
+
+        Ok(true)
     }
 
-    fn update_pdfsets_index(&self, config: &Config) -> Result<()> {
-        if let Some(lhapdf_data_path_write) = config.lhapdf_data_path_write() {
-            let lock_file = File::create(lhapdf_data_path_write.join("pdfsets.lock"))?;
-            lock_file.lock()?;
+    fn update_pdfsets_index(&self, config: &Config) -> Result<Vec<IndexConflict>> {
+        let Some(lhapdf_data_path_write) = config.lhapdf_data_path_write() else {
+            return Ok(Vec::new());
+        };
+
+        let lock_file = File::create(lhapdf_data_path_write.join("pdfsets.lock"))?;
+        lock_file.lock()?;
+
+        let (changed, conflicts) = refresh_pdfsets_index(lhapdf_data_path_write, config)?;
 
+        if changed {
             // empty the `static thread_local` variable sitting in `getPDFIndex` to trigger the
             // re-initialization of this variable
             ffi::empty_lhaindex();
+        }
 
-            // download `pdfsets.index`
-            let mut reader = get_url(config.pdfsets_index_url())?;
-            io::copy(
-                &mut reader,
-                &mut File::create(lhapdf_data_path_write.join("pdfsets.index"))?,
-            )?;
+        lock_file.unlock()?;
 
-            lock_file.unlock()?;
+        Ok(conflicts)
+    }
+
+    fn refresh_pdfsets_index(&mut self, config: &Config) -> Result<Vec<IndexConflict>> {
+        let conflicts = self.update_pdfsets_index(config)?;
+        // the new index might know LHAIDs that were missing before
+        self.missing_lhaids.clear();
+
+        Ok(conflicts)
+    }
+
+    fn pdfsets_index_conflicts(&self, config: &Config) -> Result<Vec<IndexConflict>> {
+        config.lhapdf_data_path_write().map_or_else(
+            || Ok(Vec::new()),
+            |lhapdf_data_path_write| {
+                merge_pdfsets_indices(lhapdf_data_path_write, config)
+                    .map(|(_, conflicts)| conflicts)
+            },
+        )
+    }
+
+    fn locked_set(&self, setname: &str) -> Result<LockedSet> {
+        let path = self.pdfset_path(setname)?;
+
+        Ok(LockedSet {
+            name: setname.to_owned(),
+            data_version: data_version(&path.join(format!("{setname}.info")))?,
+            sha256: lockfile::hash_directory(&path)?,
+            path,
+            members: self
+                .loaded_sets
+                .get(setname)
+                .map_or_else(Vec::new, |members| members.iter().copied().collect()),
+        })
+    }
+
+    /// Return an error if the installed copy of the PDF set `setname` doesn't have the version it
+    /// is pinned to.
+    fn check_installed_version(&self, setname: &str, config: &Config) -> Result<()> {
+        if !config.pinned_versions().contains_key(setname) {
+            return Ok(());
+        }
+
+        let path = self.pdfset_path(setname)?;
+
+        check_pinned_version(
+            setname,
+            &path.join(format!("{setname}.info")),
+            &format!("the copy in '{}'", path.display()),
+            config,
+        )
+    }
+
+    fn record(&mut self, setname: &str, member: Option<i32>, config: &Config) -> Result<()> {
+        // sets are only downloaded with the pinned version, but they may have been installed
+        // differently; checking the first time a set is loaded is enough
+        if !self.loaded_sets.contains_key(setname) {
+            self.check_installed_version(setname, config)?;
+        }
+
+        if let Some((lockfile, verified)) = &self.verification {
+            // hashing a set is expensive, so do it only once per set
+            if !verified.contains(setname) {
+                lockfile.verify(&self.locked_set(setname)?)?;
+            }
+        }
+
+        if let Some((_, verified)) = &mut self.verification {
+            verified.insert(setname.to_owned());
+        }
+
+        self.loaded_sets
+            .entry(setname.to_owned())
+            .or_default()
+            .extend(member);
+
+        Ok(())
+    }
+
+    fn lockfile(&self) -> Result<Lockfile> {
+        Ok(Lockfile {
+            sets: self
+                .loaded_sets
+                .keys()
+                .map(|setname| self.locked_set(setname))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    fn verify_against(&mut self, lockfile: Lockfile) -> Result<()> {
+        // PDF sets that were loaded before must pass as well
+        for setname in self.loaded_sets.keys() {
+            lockfile.verify(&self.locked_set(setname)?)?;
         }
 
+        self.verification = Some((lockfile, self.loaded_sets.keys().cloned().collect()));
+
         Ok(())
     }
 
@@ -284,6 +1198,23 @@ impl LhapdfData {
         unmanaged::pdfset_new(setname)
     }
 
+    fn pdfset_path(&self, setname: &str) -> Result<PathBuf> {
+        unmanaged::pdfset_path(setname, None)
+    }
+
+    unsafe fn paths_append(&self, path: &Path) -> Result<()> {
+        // SAFETY: guaranteed by the caller
+        unsafe { unmanaged::paths_append(path) }
+    }
+
+    fn find_file(&self, target: &str) -> Option<PathBuf> {
+        unmanaged::find_file(target)
+    }
+
+    fn search_paths(&self) -> Vec<PathBuf> {
+        unmanaged::search_paths()
+    }
+
     fn set_verbosity(&self, verbosity: i32) {
         unmanaged::set_verbosity(verbosity);
     }
@@ -298,23 +1229,117 @@ pub fn pdf_name_and_member_via_lhaid(lhaid: i32) -> Option<(String, i32)> {
     let config = Config::get();
 
     // TODO: change return type of this function and handle the error properly
-    let lock = LhapdfData::get().lock().unwrap();
+    let mut lock = LhapdfData::get().lock().unwrap();
 
     lock.pdf_name_and_member_via_lhaid(lhaid).or_else(|| {
+        // don't ask the server again for LHAIDs it didn't know about
+        if lock.missing_lhaids.contains(&lhaid) {
+            return None;
+        }
+
         // TODO: change return type of this function and handle the error properly
         lock.update_pdfsets_index(config).unwrap();
-        lock.pdf_name_and_member_via_lhaid(lhaid)
+        let result = lock.pdf_name_and_member_via_lhaid(lhaid);
+
+        if result.is_none() {
+            lock.missing_lhaids.insert(lhaid);
+        }
+
+        result
     })
 }
 
-pub fn pdf_with_setname_and_member(setname: &str, member: i32) -> Result<UniquePtr<PDF>> {
+pub fn pdfsets_index_conflicts() -> Result<Vec<IndexConflict>> {
     // this must be the first call before anything from LHAPDF
     let config = Config::get();
 
     // TODO: handle error properly
     let lock = LhapdfData::get().lock().unwrap();
 
-    lock.pdf_with_setname_and_member(setname, member)
+    lock.pdfsets_index_conflicts(config)
+}
+
+pub fn check_updates() -> Result<Vec<SetUpdate>> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.check_updates(config)
+}
+
+pub fn update_set(name: &str) -> Result<()> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.update_set(name, config)
+}
+
+pub fn update_pdfsets_index() -> Result<Vec<IndexConflict>> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let mut lock = LhapdfData::get().lock().unwrap();
+
+    lock.refresh_pdfsets_index(config)
+}
+
+pub fn installed_sets() -> Result<Vec<InstalledSet>> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.installed_sets(config)
+}
+
+pub fn remote_sets() -> Result<Vec<RemoteSet>> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.remote_sets(config)
+}
+
+pub fn install_set(name: &str) -> Result<PathBuf> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.install_set(name, config)
+}
+
+pub fn remove_set(name: &str) -> Result<bool> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.remove_set(name, config)
+}
+
+pub fn pdf_with_setname_and_member(setname: &str, member: i32) -> Result<UniquePtr<PDF>> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+    // without the search paths LHAPDF would download sets it can't find afterwards
+    unmanaged::require_init()?;
+
+    // TODO: handle error properly
+    let mut lock = LhapdfData::get().lock().unwrap();
+
+    let pdf = lock
+        .pdf_with_setname_and_member(setname, member)
         .or_else(|err: Error| {
             // here we rely on exactly matching LHAPDF's exception string
             if err.to_string() == format!("Info file not found for PDF set '{setname}'") {
@@ -323,17 +1348,21 @@ pub fn pdf_with_setname_and_member(setname: &str, member: i32) -> Result<UniqueP
             } else {
                 Err(err)
             }
-        })
+        })?;
+
+    lock.record(setname, Some(member), config).map(|()| pdf)
 }
 
 pub fn pdfset_new(setname: &str) -> Result<UniquePtr<PDFSet>> {
     // this must be the first call before anything from LHAPDF
     let config = Config::get();
+    // without the search paths LHAPDF would download sets it can't find afterwards
+    unmanaged::require_init()?;
 
     // TODO: handle error properly
-    let lock = LhapdfData::get().lock().unwrap();
+    let mut lock = LhapdfData::get().lock().unwrap();
 
-    lock.pdfset_new(setname).or_else(|err: Error| {
+    let pdfset = lock.pdfset_new(setname).or_else(|err: Error| {
         // here we rely on exactly matching LHAPDF's exception string
         if err.to_string() == format!("Info file not found for PDF set '{setname}'") {
             lock.download_set(setname, config)
@@ -341,7 +1370,100 @@ pub fn pdfset_new(setname: &str) -> Result<UniquePtr<PDFSet>> {
         } else {
             Err(err)
         }
-    })
+    })?;
+
+    lock.record(setname, None, config).map(|()| pdfset)
+}
+
+#[cfg(feature = "native")]
+pub fn pdfset_path(setname: &str, member: Option<i32>) -> Result<PathBuf> {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+    // without the search paths LHAPDF would download sets it can't find afterwards
+    unmanaged::require_init()?;
+
+    // TODO: handle error properly
+    let mut lock = LhapdfData::get().lock().unwrap();
+
+    let path = lock.pdfset_path(setname).or_else(|_| {
+        lock.download_set(setname, config)
+            .and_then(|()| lock.pdfset_path(setname))
+    })?;
+
+    lock.record(setname, member, config).map(|()| path)
+}
+
+pub unsafe fn init() {
+    // this must be the first call before anything from LHAPDF
+    let config = Config::get();
+
+    // TODO: handle error properly
+    let _lock = LhapdfData::get().lock().unwrap();
+
+    // let LHAPDF know where we've stored our PDFs. Sets registered with `register_set` are written
+    // into a temporary directory, which is searched first
+    let registered_sets = memory::dir();
+    let paths: Vec<_> = iter::once(registered_sets.as_path())
+        .chain(config.lhapdf_data_path_write())
+        .chain(config.lhapdf_data_path_read.iter().map(Deref::deref))
+        .collect();
+    // SAFETY: guaranteed by the caller
+    unsafe { unmanaged::init_paths(&paths) };
+}
+
+pub unsafe fn paths_append(path: &Path) -> Result<()> {
+    // this must be the first call before anything from LHAPDF
+    let _ = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    // SAFETY: guaranteed by the caller
+    unsafe { lock.paths_append(path) }
+}
+
+pub fn data_paths() -> Vec<PathBuf> {
+    unmanaged::data_paths()
+}
+
+pub fn find_file(target: &str) -> Option<PathBuf> {
+    // this must be the first call before anything from LHAPDF
+    let _ = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.find_file(target)
+}
+
+pub fn search_paths() -> Vec<PathBuf> {
+    // this must be the first call before anything from LHAPDF
+    let _ = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.search_paths()
+}
+
+pub fn lockfile() -> Result<Lockfile> {
+    // this must be the first call before anything from LHAPDF
+    let _ = Config::get();
+
+    // TODO: handle error properly
+    let lock = LhapdfData::get().lock().unwrap();
+
+    lock.lockfile()
+}
+
+pub fn verify_against(lockfile: Lockfile) -> Result<()> {
+    // this must be the first call before anything from LHAPDF
+    let _ = Config::get();
+
+    // TODO: handle error properly
+    let mut lock = LhapdfData::get().lock().unwrap();
+
+    lock.verify_against(lockfile)
 }
 
 pub fn set_verbosity(verbosity: i32) {
@@ -363,3 +1485,87 @@ pub fn verbosity() -> i32 {
 
     lock.verbosity()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_conflicting_sources() {
+        let first = Url::parse("https://example.com/first.index").unwrap();
+        let second = Url::parse("file:///second.index").unwrap();
+        let mirror = Url::parse("https://example.com/mirror.index").unwrap();
+        let partial = Url::parse("https://example.com/partial.index").unwrap();
+        let private = Url::parse("file:///private.index").unwrap();
+
+        let (merged, conflicts) = merge_index_sources(&[
+            (&first, "100 SetA 1\n200 SetB 1\n300 SetC 1\n".to_owned()),
+            // the range of `SetF` contains `SetA`, `SetD` and `SetE` fall into the ranges of
+            // `SetA` and `SetB`
+            (
+                &second,
+                "40 SetF 1\n150 SetD 1\n250 SetE 1\n400 SetG 1\n".to_owned(),
+            ),
+            // `SetH` has the same LHAID as `SetB`
+            (&mirror, "100 SetA 1\n200 SetH 1\n300 SetC 1\n".to_owned()),
+            // a mirror that doesn't list `SetB` and therefore doesn't know the end of `SetA`
+            (&partial, "100 SetA 1\n300 SetC 1\n".to_owned()),
+            // `SetI` falls into the range of `SetA`, which in this source only partially overlaps the
+            // range `SetA` has in the first source
+            (&private, "100 SetA 1\n170 SetI 1\n".to_owned()),
+        ]);
+
+        assert_eq!(
+            merged,
+            "40 SetF 1\n100 SetA 1\n200 SetB 1\n300 SetC 1\n400 SetG 1\n"
+        );
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| (
+                    conflict.lhaid,
+                    conflict.setname.as_str(),
+                    &conflict.source,
+                    conflict.ignored_setname.as_str(),
+                    &conflict.ignored_source
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (100, "SetA", &first, "SetF", &second),
+                (150, "SetA", &first, "SetD", &second),
+                (250, "SetB", &first, "SetE", &second),
+                (200, "SetB", &first, "SetH", &mirror),
+                (170, "SetA", &first, "SetI", &private),
+            ]
+        );
+    }
+
+    #[test]
+    fn lhapdf_conf_uses_lhapdf_names() {
+        for (interpolator, name) in [
+            (Interpolator::LogBicubic, "logcubic"),
+            (Interpolator::LogBilinear, "log"),
+            (Interpolator::Bicubic, "cubic"),
+            (Interpolator::Bilinear, "linear"),
+            (Interpolator::Nearest, "nearest"),
+        ] {
+            let settings: LhapdfSettings =
+                toml::from_str(&format!("Interpolator = \"{name}\"")).unwrap();
+            let conf = settings.to_lhapdf_conf();
+            let value = conf
+                .lines()
+                .find_map(|line| line.strip_prefix("Interpolator: "))
+                .unwrap();
+
+            assert_eq!(settings.interpolator(), interpolator);
+            assert_eq!(value, name);
+            assert_eq!(value.parse::<Interpolator>().unwrap(), interpolator);
+        }
+
+        // the default is the one earlier versions wrote without the marker
+        assert!(UNMARKED_LHAPDF_CONF.contains(&format!(
+            "\nInterpolator: {}\n",
+            LhapdfSettings::default().interpolator()
+        )));
+    }
+}
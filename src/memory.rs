@@ -0,0 +1,89 @@
+use super::Result;
+use super::grid::{Info, Member};
+use super::writer::PdfSetWriter;
+use std::collections::BTreeMap;
+#[cfg(feature = "lhapdf")]
+use std::fs;
+#[cfg(feature = "lhapdf")]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Contents of a PDF set registered with [`register_set`].
+// ALLOW: without the `native` feature registered sets are only loaded by LHAPDF from disk
+#[cfg_attr(not(feature = "native"), allow(dead_code))]
+pub struct MemorySet {
+    pub info: Info,
+    pub members: Vec<Member>,
+}
+
+#[derive(Default)]
+struct Registry {
+    sets: BTreeMap<String, Arc<MemorySet>>,
+}
+
+/// Return the directory into which registered sets are written for LHAPDF. It's added in front of
+/// LHAPDF's search paths by [`crate::init`].
+#[cfg(feature = "lhapdf")]
+pub fn dir() -> PathBuf {
+    std::env::temp_dir().join(format!("managed-lhapdf-{}", std::process::id()))
+}
+
+impl Registry {
+    fn get() -> &'static Mutex<Self> {
+        static SINGLETON: OnceLock<Mutex<Registry>> = OnceLock::new();
+        SINGLETON.get_or_init(Mutex::default)
+    }
+
+    fn insert(&mut self, writer: &PdfSetWriter) -> Result<()> {
+        let set = Arc::new(MemorySet {
+            info: writer.set_info()?,
+            members: writer.members().to_vec(),
+        });
+
+        // LHAPDF wouldn't find the set without the temporary directory in its search paths
+        #[cfg(feature = "lhapdf")]
+        super::unmanaged::require_init()?;
+
+        // `write` creates the directory if it was removed after the last set was unregistered
+        #[cfg(feature = "lhapdf")]
+        writer.write(&dir())?;
+
+        self.sets.insert(writer.name().to_owned(), set);
+
+        Ok(())
+    }
+
+    // ALLOW: only removing the files written for LHAPDF can fail
+    #[cfg_attr(not(feature = "lhapdf"), allow(clippy::unnecessary_wraps))]
+    fn remove(&mut self, name: &str) -> Result<bool> {
+        if self.sets.remove(name).is_none() {
+            return Ok(false);
+        }
+
+        // don't leave the temporary directory behind once the last set is gone
+        #[cfg(feature = "lhapdf")]
+        if self.sets.is_empty() {
+            fs::remove_dir_all(dir())?;
+        } else {
+            fs::remove_dir_all(dir().join(name))?;
+        }
+
+        Ok(true)
+    }
+}
+
+pub fn register_set(writer: &PdfSetWriter) -> Result<()> {
+    // TODO: handle error properly
+    Registry::get().lock().unwrap().insert(writer)
+}
+
+pub fn unregister_set(name: &str) -> Result<bool> {
+    // TODO: handle error properly
+    Registry::get().lock().unwrap().remove(name)
+}
+
+#[cfg(feature = "native")]
+pub fn set(name: &str) -> Option<Arc<MemorySet>> {
+    // TODO: handle error properly
+    Registry::get().lock().unwrap().sets.get(name).cloned()
+}
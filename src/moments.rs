@@ -0,0 +1,207 @@
+//! Moments of PDFs and checks of their sum rules.
+//!
+//! All integrals run over `x` from the minimum `x` value of the PDF to `1`, so that the sum rules
+//! are only fulfilled up to the contribution from smaller values of `x`.
+
+use super::backend::PdfBackend;
+use super::integration;
+use std::fmt::{self, Display, Formatter};
+
+/// Relative accuracy of the integration.
+const TOLERANCE: f64 = 1e-8;
+
+/// Sum rule that the members of a PDF set should fulfil.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SumRule {
+    /// The momentum fractions of all flavours add up to one.
+    Momentum,
+    /// The number of valence quarks with the given PDG ID, which is the integral of the difference
+    /// between quark and antiquark.
+    Valence(i32),
+}
+
+impl SumRule {
+    /// Return the value of the integral that this sum rule expects for a proton.
+    #[must_use]
+    pub const fn expected(self) -> f64 {
+        match self {
+            Self::Momentum | Self::Valence(1) => 1.0,
+            Self::Valence(2) => 2.0,
+            Self::Valence(_) => 0.0,
+        }
+    }
+
+    /// Evaluate the integral of this sum rule for `pdf` at `q2`.
+    pub fn eval<P: PdfBackend + ?Sized>(self, pdf: &P, q2: f64) -> f64 {
+        match self {
+            Self::Momentum => momentum_sum_rule(pdf, q2),
+            Self::Valence(pid) => valence_sum_rule(pdf, pid, q2),
+        }
+    }
+}
+
+impl Display for SumRule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Momentum => write!(f, "momentum sum rule"),
+            Self::Valence(pid) => write!(f, "valence sum rule of {pid}"),
+        }
+    }
+}
+
+/// Sum rule that a member of a PDF set violates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SumRuleViolation {
+    /// Index of the member.
+    pub member: usize,
+    /// The violated sum rule.
+    pub rule: SumRule,
+    /// The value of the integral.
+    pub value: f64,
+    /// The value expected by the sum rule.
+    pub expected: f64,
+}
+
+/// Calculate the Mellin moment `∫ x^(n - 1) f(x) dx` of the flavour `id` of `pdf` at `q2`.
+///
+/// The second moment is the momentum fraction carried by the flavour, and the first moment the
+/// number of partons, which only exists for valence distributions.
+///
+/// # Panics
+///
+/// If `q2` isn't within the boundaries of `pdf` this function may panic.
+pub fn moment<P: PdfBackend + ?Sized>(pdf: &P, id: i32, n: i32, q2: f64) -> f64 {
+    integrate(pdf, q2, |x, xfx| x.powi(n - 1) * xfx(id))
+}
+
+/// Calculate the sum of the momentum fractions `∫ x f(x) dx` of all flavours of `pdf` at `q2`.
+///
+/// # Panics
+///
+/// If `q2` isn't within the boundaries of `pdf` this function may panic.
+pub fn momentum_sum_rule<P: PdfBackend + ?Sized>(pdf: &P, q2: f64) -> f64 {
+    let flavors = pdf.flavors();
+
+    integrate(pdf, q2, |x, xfx| {
+        x * flavors.iter().map(|&id| xfx(id)).sum::<f64>()
+    })
+}
+
+/// Calculate the number of valence quarks `∫ (f_q(x) - f_qbar(x)) dx` of the quark with PDG ID
+/// `pid` of `pdf` at `q2`.
+///
+/// # Panics
+///
+/// If `q2` isn't within the boundaries of `pdf` this function may panic.
+pub fn valence_sum_rule<P: PdfBackend + ?Sized>(pdf: &P, pid: i32, q2: f64) -> f64 {
+    integrate(pdf, q2, |_, xfx| xfx(pid) - xfx(-pid))
+}
+
+/// Return the sum rules that the members in `pdfs` violate at `q2`.
+///
+/// The momentum sum rule and the valence sum rules of the up, down and strange quarks are checked
+/// for every member, and reported if they deviate by more than `tolerance` from their expected
+/// values.
+///
+/// # Panics
+///
+/// If `q2` isn't within the boundaries of a member this function may panic.
+pub fn sum_rule_violations<P: PdfBackend>(
+    pdfs: &[P],
+    q2: f64,
+    tolerance: f64,
+) -> Vec<SumRuleViolation> {
+    let mut violations = Vec::new();
+
+    for (member, pdf) in pdfs.iter().enumerate() {
+        let flavors = pdf.flavors();
+        let rules = [
+            SumRule::Momentum,
+            SumRule::Valence(2),
+            SumRule::Valence(1),
+            SumRule::Valence(3),
+        ];
+
+        for rule in rules {
+            if let SumRule::Valence(pid) = rule
+                && (!flavors.contains(&pid) || !flavors.contains(&-pid))
+            {
+                continue;
+            }
+
+            let value = rule.eval(pdf, q2);
+            let expected = rule.expected();
+
+            if (value - expected).abs() > tolerance {
+                violations.push(SumRuleViolation {
+                    member,
+                    rule,
+                    value,
+                    expected,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Integrate `integrand(x, xfx) / x` over `x`, where `xfx` returns `x * f(x)` for a given PDG ID.
+fn integrate<P: PdfBackend + ?Sized>(
+    pdf: &P,
+    q2: f64,
+    integrand: impl Fn(f64, &dyn Fn(i32) -> f64) -> f64,
+) -> f64 {
+    // substitute `x = exp(y)`, which cancels the `1 / x` and flattens the integrand at small `x`
+    integration::integrate(
+        |y| {
+            let x = y.exp();
+            integrand(x, &|id| pdf.xfx_q2(id, x, q2))
+        },
+        pdf.x_min().ln(),
+        0.0,
+        TOLERANCE,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::backend::Toy;
+    use super::*;
+    use std::slice;
+
+    #[test]
+    fn moments_and_sum_rules() {
+        // the up and down valence distributions fulfil their sum rules for `x_min -> 0`, there's a
+        // strange asymmetry and no gluons, so that the momentum sum rule is violated
+        let toy = Toy::new(vec![-3, -2, -1, 1, 2, 3, 21], 1e-12, |id, x, _| {
+            let valence = |norm: f64| norm * x.sqrt() * (1.0 - x).powi(3);
+            let sea = 0.05 * (1.0 - x).powi(3);
+
+            // the valence part integrates to `norm * 32 / 35`
+            match id {
+                2 => valence(70.0 / 32.0) + sea,
+                1 => valence(35.0 / 32.0) + sea,
+                3 => valence(0.1) + sea,
+                -3..=-1 => sea,
+                21 => 0.0,
+                _ => unreachable!(),
+            }
+        });
+        let q2 = 100.0;
+
+        // `∫ (1 - x)^3 dx = 1 / 4`
+        assert!((moment(&toy, -1, 2, q2) - 0.05 / 4.0).abs() < 1e-10);
+        assert!((valence_sum_rule(&toy, 2, q2) - 2.0).abs() < 1e-5);
+        assert!((valence_sum_rule(&toy, 1, q2) - 1.0).abs() < 1e-5);
+
+        let violations = sum_rule_violations(slice::from_ref(&toy), q2, 1e-3);
+
+        // the momentum fractions of the valence quarks don't add up to one
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].rule, SumRule::Momentum);
+        assert_eq!(violations[1].rule, SumRule::Valence(3));
+        assert!((violations[1].value - 0.1 * 32.0 / 35.0).abs() < 1e-5);
+        assert!(violations[1].expected.abs() < 1e-14);
+    }
+}
@@ -0,0 +1,972 @@
+//! Pure-Rust evaluation of PDF members. This reproduces LHAPDF's log-bicubic interpolation, its
+//! continuation extrapolation and the interpolated strong coupling (`AlphaS_Type: ipol`) without
+//! calling the C++ library.
+
+use super::backend::{self, PdfBackend};
+use super::basis;
+use super::grid::{GridSet, Info, Member, SubGrid};
+use super::{Error, Result};
+use std::fmt::{self, Formatter};
+use std::path::PathBuf;
+
+/// A subgrid of a member with the logarithms of its knots precomputed.
+#[derive(Clone)]
+struct KnotArray {
+    xs: Vec<f64>,
+    logxs: Vec<f64>,
+    q2s: Vec<f64>,
+    logq2s: Vec<f64>,
+    nflavors: usize,
+    values: Vec<f64>,
+}
+
+impl KnotArray {
+    fn new(subgrid: &SubGrid) -> Self {
+        // LHAPDF squares the knots in `Q` and stores them as `Q^2`
+        let q2s: Vec<_> = subgrid.q().iter().map(|q| q * q).collect();
+
+        Self {
+            xs: subgrid.x().to_vec(),
+            logxs: subgrid.x().iter().map(|x| x.ln()).collect(),
+            logq2s: q2s.iter().map(|q2| q2.ln()).collect(),
+            q2s,
+            nflavors: subgrid.flavors().len(),
+            values: subgrid.values().to_vec(),
+        }
+    }
+
+    fn xf(&self, ix: usize, iq2: usize, ifl: usize) -> f64 {
+        self.values[(ix * self.q2s.len() + iq2) * self.nflavors + ifl]
+    }
+
+    /// Derivative of `xf` with respect to `ln(x)` at the knot `ix`.
+    fn ddlogx(&self, ix: usize, iq2: usize, ifl: usize) -> f64 {
+        let nx = self.xs.len();
+
+        if ix == 0 {
+            (self.xf(1, iq2, ifl) - self.xf(0, iq2, ifl)) / (self.logxs[1] - self.logxs[0])
+        } else if ix == nx - 1 {
+            (self.xf(ix, iq2, ifl) - self.xf(ix - 1, iq2, ifl))
+                / (self.logxs[ix] - self.logxs[ix - 1])
+        } else {
+            let lddx = (self.xf(ix, iq2, ifl) - self.xf(ix - 1, iq2, ifl))
+                / (self.logxs[ix] - self.logxs[ix - 1]);
+            let rddx = (self.xf(ix + 1, iq2, ifl) - self.xf(ix, iq2, ifl))
+                / (self.logxs[ix + 1] - self.logxs[ix]);
+            f64::midpoint(lddx, rddx)
+        }
+    }
+
+    /// Cubic interpolation in `ln(x)` along the knots `iq2` in `Q^2`, or its derivative with
+    /// respect to `ln(x)`.
+    fn interpolate_x(
+        &self,
+        tlogx: f64,
+        ix: usize,
+        iq2: usize,
+        ifl: usize,
+        derivative: Derivative,
+    ) -> f64 {
+        let dlogx = self.logxs[ix + 1] - self.logxs[ix];
+        let vl = self.xf(ix, iq2, ifl);
+        let vdl = self.ddlogx(ix, iq2, ifl) * dlogx;
+        let vh = self.xf(ix + 1, iq2, ifl);
+        let vdh = self.ddlogx(ix + 1, iq2, ifl) * dlogx;
+
+        if derivative == Derivative::LogX {
+            interpolate_cubic_derivative(tlogx, vl, vdl, vh, vdh) / dlogx
+        } else {
+            interpolate_cubic(tlogx, vl, vdl, vh, vdh)
+        }
+    }
+
+    /// The log-bicubic interpolation of LHAPDF, which falls back to a log-bilinear interpolation
+    /// if there are fewer than four knots in `Q^2`, or the derivative of the interpolating
+    /// polynomial.
+    fn interpolate(&self, x: f64, q2: f64, ifl: usize, derivative: Derivative) -> f64 {
+        let ix = index_below(&self.xs, x);
+        let iq2 = index_below(&self.q2s, q2);
+        let logx = x.ln();
+        let logq2 = q2.ln();
+
+        if self.q2s.len() < 4 {
+            let linear_x = |iq2| {
+                if derivative == Derivative::LogX {
+                    (self.xf(ix + 1, iq2, ifl) - self.xf(ix, iq2, ifl))
+                        / (self.logxs[ix + 1] - self.logxs[ix])
+                } else {
+                    interpolate_linear(
+                        logx,
+                        self.logxs[ix],
+                        self.logxs[ix + 1],
+                        self.xf(ix, iq2, ifl),
+                        self.xf(ix + 1, iq2, ifl),
+                    )
+                }
+            };
+            let lower = linear_x(iq2);
+            let upper = linear_x(iq2 + 1);
+
+            if derivative == Derivative::LogQ2 {
+                return (upper - lower) / (self.logq2s[iq2 + 1] - self.logq2s[iq2]);
+            }
+
+            return interpolate_linear(logq2, self.logq2s[iq2], self.logq2s[iq2 + 1], lower, upper);
+        }
+
+        let nq2 = self.q2s.len();
+        let tlogx = (logx - self.logxs[ix]) / (self.logxs[ix + 1] - self.logxs[ix]);
+        let dlogq_1 = self.logq2s[iq2 + 1] - self.logq2s[iq2];
+        let t = (logq2 - self.logq2s[iq2]) / dlogq_1;
+        // the derivatives in `ln(Q^2)` below are linear in these values, so replacing them with
+        // their derivatives in `ln(x)` gives the derivative of the result
+        let interpolate_x = |iq2| self.interpolate_x(tlogx, ix, iq2, ifl, derivative);
+
+        let vl = interpolate_x(iq2);
+        let vh = interpolate_x(iq2 + 1);
+
+        // derivatives in `ln(Q^2)` from finite differences of the interpolations in `x`
+        let (vdl, vdh) = if iq2 == 0 {
+            let dlogq_2 = self.logq2s[iq2 + 2] - self.logq2s[iq2 + 1];
+            let vhh = interpolate_x(iq2 + 2);
+            let vdl = (vh - vl) / dlogq_1;
+            (vdl, f64::midpoint(vdl, (vhh - vh) / dlogq_2))
+        } else if iq2 + 1 == nq2 - 1 {
+            let dlogq_0 = self.logq2s[iq2] - self.logq2s[iq2 - 1];
+            let vll = interpolate_x(iq2 - 1);
+            let vdh = (vh - vl) / dlogq_1;
+            (f64::midpoint(vdh, (vl - vll) / dlogq_0), vdh)
+        } else {
+            let dlogq_0 = self.logq2s[iq2] - self.logq2s[iq2 - 1];
+            let dlogq_2 = self.logq2s[iq2 + 2] - self.logq2s[iq2 + 1];
+            let vll = interpolate_x(iq2 - 1);
+            let vhh = interpolate_x(iq2 + 2);
+            (
+                f64::midpoint((vh - vl) / dlogq_1, (vl - vll) / dlogq_0),
+                f64::midpoint((vh - vl) / dlogq_1, (vhh - vh) / dlogq_2),
+            )
+        };
+
+        if derivative == Derivative::LogQ2 {
+            return interpolate_cubic_derivative(t, vl, vdl * dlogq_1, vh, vdh * dlogq_1) / dlogq_1;
+        }
+
+        interpolate_cubic(t, vl, vdl * dlogq_1, vh, vdh * dlogq_1)
+    }
+}
+
+/// Variable with respect to which the interpolating polynomial is differentiated.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Derivative {
+    /// The polynomial isn't differentiated.
+    None,
+    /// Derivative with respect to `ln(x)`.
+    LogX,
+    /// Derivative with respect to `ln(Q^2)`.
+    LogQ2,
+}
+
+/// Return the index of the knot at or below `value`, which is never the index of the last knot.
+fn index_below(knots: &[f64], value: f64) -> usize {
+    // this is `std::upper_bound`
+    let index = knots.partition_point(|&knot| knot <= value);
+
+    index.min(knots.len() - 1) - 1
+}
+
+// ALLOW: the operations are the same as in LHAPDF, so that the results agree to the last digits
+#[allow(clippy::suboptimal_flops)]
+fn interpolate_linear(x: f64, xl: f64, xh: f64, yl: f64, yh: f64) -> f64 {
+    yl + (x - xl) / (xh - xl) * (yh - yl)
+}
+
+// ALLOW: the operations are the same as in LHAPDF, so that the results agree to the last digits
+#[allow(clippy::suboptimal_flops)]
+fn interpolate_cubic(t: f64, vl: f64, vdl: f64, vh: f64, vdh: f64) -> f64 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    let p0 = (2.0 * t3 - 3.0 * t2 + 1.0) * vl;
+    let m0 = (t3 - 2.0 * t2 + t) * vdl;
+    let p1 = (-2.0 * t3 + 3.0 * t2) * vh;
+    let m1 = (t3 - t2) * vdh;
+
+    p0 + m0 + p1 + m1
+}
+
+/// Derivative of [`interpolate_cubic`] with respect to `t`.
+fn interpolate_cubic_derivative(t: f64, vl: f64, vdl: f64, vh: f64, vdh: f64) -> f64 {
+    let t2 = t * t;
+
+    let dp = 6.0 * (t2 - t);
+    let dm0 = 3.0f64.mul_add(t2, 4.0f64.mul_add(-t, 1.0));
+    let dm1 = 3.0f64.mul_add(t2, -2.0 * t);
+
+    dp.mul_add(vl - vh, dm0.mul_add(vdl, dm1 * vdh))
+}
+
+/// Extrapolate linearly in `x`. If both values are sufficiently positive, `ln(y)` is extrapolated
+/// instead of `y` to keep the result positive.
+// ALLOW: the operations are the same as in LHAPDF, so that the results agree to the last digits
+#[allow(clippy::suboptimal_flops)]
+fn extrapolate_linear(x: f64, xl: f64, xh: f64, yl: f64, yh: f64) -> f64 {
+    if yl > 1e-3 && yh > 1e-3 {
+        (yl.ln() + (x - xl) / (xh - xl) * (yh.ln() - yl.ln())).exp()
+    } else {
+        interpolate_linear(x, xl, xh, yl, yh)
+    }
+}
+
+/// A subgrid of the interpolated strong coupling.
+#[derive(Clone)]
+struct AlphaSArray {
+    q2s: Vec<f64>,
+    logq2s: Vec<f64>,
+    alphas: Vec<f64>,
+}
+
+impl AlphaSArray {
+    fn ddlogq_forward(&self, i: usize) -> f64 {
+        (self.alphas[i + 1] - self.alphas[i]) / (self.logq2s[i + 1] - self.logq2s[i])
+    }
+
+    fn ddlogq_backward(&self, i: usize) -> f64 {
+        (self.alphas[i] - self.alphas[i - 1]) / (self.logq2s[i] - self.logq2s[i - 1])
+    }
+
+    fn ddlogq_central(&self, i: usize) -> f64 {
+        0.5 * (self.ddlogq_forward(i) + self.ddlogq_backward(i))
+    }
+}
+
+/// The strong coupling interpolated from the values given by `AlphaS_Qs` and `AlphaS_Vals`.
+#[derive(Clone)]
+struct AlphaSIpol {
+    q2s: Vec<f64>,
+    alphas: Vec<f64>,
+    // a repeated knot starts a new subgrid, which happens at flavour thresholds
+    arrays: Vec<AlphaSArray>,
+}
+
+// ALLOW: repeated knots, which separate the subgrids, are exact copies of each other
+#[allow(clippy::float_cmp)]
+impl AlphaSIpol {
+    fn new(qs: &[f64], alphas: Vec<f64>) -> Result<Self> {
+        if qs.len() != alphas.len() || qs.len() < 2 {
+            return Err(Error::General(
+                "'AlphaS_Qs' and 'AlphaS_Vals' must have the same number of at least two values"
+                    .to_owned(),
+            ));
+        }
+
+        let q2s: Vec<_> = qs.iter().map(|q| q * q).collect();
+
+        if !q2s.windows(2).all(|q2s| q2s[0] <= q2s[1]) {
+            return Err(Error::General("'AlphaS_Qs' are not increasing".to_owned()));
+        }
+
+        let mut arrays = Vec::new();
+        let mut start = 0;
+
+        for end in 1..=q2s.len() {
+            if end == q2s.len() || q2s[end] == q2s[end - 1] {
+                if end - start < 2 {
+                    return Err(Error::General(
+                        "subgrids of 'AlphaS_Qs' need at least two values".to_owned(),
+                    ));
+                }
+
+                arrays.push(AlphaSArray {
+                    q2s: q2s[start..end].to_vec(),
+                    logq2s: q2s[start..end].iter().map(|q2| q2.ln()).collect(),
+                    alphas: alphas[start..end].to_vec(),
+                });
+                start = end;
+            }
+        }
+
+        Ok(Self {
+            q2s,
+            alphas,
+            arrays,
+        })
+    }
+
+    fn alphas_q2(&self, q2: f64) -> f64 {
+        assert!(q2 >= 0.0, "Unphysical Q2 given: {q2}");
+
+        // UNWRAP: the constructor guarantees at least two knots
+        let (&q2_first, &q2_last) = self.q2s.first().zip(self.q2s.last()).unwrap();
+
+        if q2 < q2_first {
+            // power-law extrapolation, which is linear in a log-log plot
+            let next = self.q2s.iter().position(|&q2| q2 != q2_first).unwrap();
+            let dlogq2 = (self.q2s[next] / q2_first).log10();
+            let dlogas = (self.alphas[next] / self.alphas[0]).log10();
+
+            return self.alphas[0] * (q2 / q2_first).powf(dlogas / dlogq2);
+        }
+
+        if q2 > q2_last {
+            return self.alphas[self.alphas.len() - 1];
+        }
+
+        let index = self.arrays.partition_point(|array| array.q2s[0] <= q2);
+        let array = &self.arrays[index.max(1) - 1];
+        let i = index_below(&array.q2s, q2);
+        let last = array.q2s.len() - 1;
+
+        let (dlower, dupper) = if last == 1 {
+            (array.ddlogq_forward(0), array.ddlogq_backward(1))
+        } else if i == 0 {
+            (array.ddlogq_forward(i), array.ddlogq_central(i + 1))
+        } else if i == last - 1 {
+            (array.ddlogq_central(i), array.ddlogq_backward(i + 1))
+        } else {
+            (array.ddlogq_central(i), array.ddlogq_central(i + 1))
+        };
+
+        let dlogq2 = array.logq2s[i + 1] - array.logq2s[i];
+        let t = (q2.ln() - array.logq2s[i]) / dlogq2;
+
+        interpolate_cubic(
+            t,
+            array.alphas[i],
+            dlower * dlogq2,
+            array.alphas[i + 1],
+            dupper * dlogq2,
+        )
+    }
+}
+
+/// A PDF member evaluated in Rust, without LHAPDF.
+///
+/// The grid is interpolated log-bicubically and
+/// extrapolated with LHAPDF's continuation extrapolator, and the strong coupling is interpolated
+/// from the values in the metadata. Evaluate it with the methods of [`PdfBackend`].
+#[derive(Clone)]
+pub struct NativePdf {
+    setname: String,
+    flavors: Vec<i32>,
+    grids: Vec<KnotArray>,
+    x_knots: Vec<f64>,
+    q2_knots: Vec<f64>,
+    alphas: Option<AlphaSIpol>,
+    force_positive: i32,
+}
+
+impl fmt::Debug for NativePdf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("NativePdf")
+            .field("setname", &self.setname)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NativePdf {
+    /// Constructor. Read the member `member` of the PDF set `setname` from the same directories
+    /// [`Pdf`](super::Pdf) would use, downloading the set if needed. Sets registered with
+    /// [`register_set`](super::register_set) are read from memory.
+    ///
+    /// # Errors
+    ///
+    /// If the set or member was not found or can not be interpolated an error is returned.
+    pub fn with_setname_and_member(setname: &str, member: i32) -> Result<Self> {
+        if let Some(set) = super::memory::set(setname) {
+            return usize::try_from(member)
+                .ok()
+                .and_then(|index| set.members.get(index))
+                .ok_or_else(|| {
+                    Error::General(format!(
+                        "PDF {setname}/{member} is out of the member range of set {setname}"
+                    ))
+                })
+                .and_then(|member| Self::from_member(setname, &set.info, member));
+        }
+
+        let set = GridSet::open(&pdfset_path(setname, member)?)?;
+        let member = usize::try_from(member).map_err(|_| {
+            Error::General(format!(
+                "PDF {setname}/{member} is out of the member range of set {setname}"
+            ))
+        })?;
+
+        Self::from_set(&set, member)
+    }
+
+    /// Constructor. Read the member `member` of `set`.
+    ///
+    /// # Errors
+    ///
+    /// If the member doesn't exist or can not be interpolated an error is returned.
+    pub fn from_set(set: &GridSet, member: usize) -> Result<Self> {
+        Self::from_member(set.name(), set.info(), &set.member(member)?)
+    }
+
+    /// Constructor. Create a PDF from the grids in `member` and the metadata of its set,
+    /// `set_info`. Entries in the header of `member` take precedence over the ones in `set_info`.
+    ///
+    /// # Errors
+    ///
+    /// If the grids of the member are invalid or have fewer than four knots in `x` an error is
+    /// returned.
+    pub fn from_member(setname: &str, set_info: &Info, member: &Member) -> Result<Self> {
+        member.validate()?;
+
+        if member
+            .subgrids()
+            .iter()
+            .any(|subgrid| subgrid.x().len() < 4)
+        {
+            return Err(Error::General(
+                "log-bicubic interpolation needs at least four knots in x".to_owned(),
+            ));
+        }
+
+        let grids: Vec<_> = member.subgrids().iter().map(KnotArray::new).collect();
+        // `validate` makes sure that all subgrids have the same knots in `x`
+        let x_knots = grids[0].xs.clone();
+        let mut q2_knots: Vec<_> = grids.iter().flat_map(|grid| &grid.q2s).copied().collect();
+        q2_knots.sort_by(f64::total_cmp);
+        q2_knots.dedup();
+
+        let entry = |key: &str| {
+            if member.info().get(key).is_some() {
+                member.info()
+            } else {
+                set_info
+            }
+        };
+
+        let alphas = if entry("AlphaS_Type")
+            .get_as::<String>("AlphaS_Type")?
+            .as_deref()
+            == Some("ipol")
+        {
+            let qs = entry("AlphaS_Qs").get_list("AlphaS_Qs")?;
+            let values = entry("AlphaS_Vals").get_list("AlphaS_Vals")?;
+
+            Some(AlphaSIpol::new(
+                &qs.unwrap_or_default(),
+                values.unwrap_or_default(),
+            )?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            setname: setname.to_owned(),
+            // UNWRAP: `validate` makes sure there's at least one subgrid
+            flavors: member.subgrids()[0].flavors().to_vec(),
+            grids,
+            x_knots,
+            q2_knots,
+            alphas,
+            force_positive: entry("ForcePositive")
+                .get_as("ForcePositive")?
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Return the name of the PDF set this member belongs to.
+    #[must_use]
+    pub fn setname(&self) -> &str {
+        &self.setname
+    }
+
+    /// Set whether the PDF will only return positive (definite) values or not, see
+    /// [`Pdf::force_positive`](super::Pdf::force_positive).
+    pub const fn set_force_positive(&mut self, mode: i32) {
+        self.force_positive = mode;
+    }
+
+    /// Check whether the PDF is set to only return positive (definite) values or not.
+    #[must_use]
+    pub const fn force_positive(&self) -> i32 {
+        self.force_positive
+    }
+
+    fn interpolate(&self, ifl: usize, x: f64, q2: f64) -> f64 {
+        self.interpolate_derivative(ifl, x, q2, Derivative::None)
+    }
+
+    fn interpolate_derivative(&self, ifl: usize, x: f64, q2: f64, derivative: Derivative) -> f64 {
+        // the subgrid whose lowest knot is the closest one at or below `q2`
+        let index = self.grids.partition_point(|grid| grid.q2s[0] <= q2);
+
+        self.grids[index.max(1) - 1].interpolate(x, q2, ifl, derivative)
+    }
+
+    /// Return the derivative of the interpolating polynomial of the flavour `id`, or `None` if
+    /// the point is outside of the grid.
+    fn derivative(&self, id: i32, x: f64, q2: f64, derivative: Derivative) -> Option<f64> {
+        let id = basis::canonical_pid(id);
+        let Some(ifl) = self
+            .flavors
+            .iter()
+            .position(|&flavor| basis::canonical_pid(flavor) == id)
+        else {
+            return Some(0.0);
+        };
+
+        ((self.x_min()..=self.x_max()).contains(&x)
+            && (self.q2_min()..=self.q2_max()).contains(&q2))
+        .then(|| self.interpolate_derivative(ifl, x, q2, derivative))
+    }
+
+    /// LHAPDF's continuation extrapolator.
+    fn extrapolate(&self, ifl: usize, x: f64, q2: f64) -> f64 {
+        let x_min = self.x_knots[0];
+        let x_min1 = self.x_knots[1];
+        let x_max = self.x_knots[self.x_knots.len() - 1];
+        let q2_min = self.q2_knots[0];
+        let q2_max = self.q2_knots[self.q2_knots.len() - 1];
+        let q2_max1 = self.q2_knots[self.q2_knots.len() - 2];
+        let ipol = |x, q2| self.interpolate(ifl, x, q2);
+        let extrapolate_x =
+            |fx_min, fx_min1| extrapolate_linear(x.ln(), x_min.ln(), x_min1.ln(), fx_min, fx_min1);
+        let extrapolate_q2 = |fq2_max, fq2_max1| {
+            extrapolate_linear(q2.ln(), q2_max.ln(), q2_max1.ln(), fq2_max, fq2_max1)
+        };
+
+        if x < x_min && (q2_min..=q2_max).contains(&q2) {
+            extrapolate_x(ipol(x_min, q2), ipol(x_min1, q2))
+        } else if (x_min..=x_max).contains(&x) && q2 > q2_max {
+            extrapolate_q2(ipol(x, q2_max), ipol(x, q2_max1))
+        } else if x < x_min && q2 > q2_max {
+            extrapolate_x(
+                extrapolate_q2(ipol(x_min, q2_max), ipol(x_min, q2_max1)),
+                extrapolate_q2(ipol(x_min1, q2_max), ipol(x_min1, q2_max1)),
+            )
+        } else if q2 < q2_min && x <= x_max {
+            // keep the anomalous dimension `d ln(xf) / d ln(Q^2)` at `q2_min` constant
+            let q2_min1 = q2_min * 1.01;
+            let (fq2_min, fq2_min1) = if x < x_min {
+                (
+                    extrapolate_x(ipol(x_min, q2_min), ipol(x_min1, q2_min)),
+                    extrapolate_x(ipol(x_min, q2_min1), ipol(x_min1, q2_min1)),
+                )
+            } else {
+                (ipol(x, q2_min), ipol(x, q2_min1))
+            };
+
+            let anom = if fq2_min.abs() >= 1e-5 {
+                ((fq2_min1 - fq2_min) / fq2_min / 0.01).max(-2.5)
+            } else {
+                1.0
+            };
+
+            fq2_min * (q2 / q2_min).powf(anom * q2 / q2_min + 1.0 - q2 / q2_min)
+        } else {
+            panic!("Point x={x}, Q2={q2} is outside the PDF grid boundaries");
+        }
+    }
+}
+
+impl PdfBackend for NativePdf {
+    fn xfx_q2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&x), "Unphysical x given: {x}");
+        assert!(q2 >= 0.0, "Unphysical Q2 given: {q2}");
+
+        // LHAPDF accepts 0 as an alternative PDG ID of the gluon, and some sets list it as such
+        let id = basis::canonical_pid(id);
+
+        let Some(ifl) = self
+            .flavors
+            .iter()
+            .position(|&flavor| basis::canonical_pid(flavor) == id)
+        else {
+            return 0.0;
+        };
+
+        let xfx = if (self.x_min()..=self.x_max()).contains(&x)
+            && (self.q2_min()..=self.q2_max()).contains(&q2)
+        {
+            self.interpolate(ifl, x, q2)
+        } else {
+            self.extrapolate(ifl, x, q2)
+        };
+
+        match self.force_positive {
+            1 => {
+                if xfx < 0.0 {
+                    0.0
+                } else {
+                    xfx
+                }
+            }
+            2 => {
+                if xfx < 1e-10 {
+                    1e-10
+                } else {
+                    xfx
+                }
+            }
+            _ => xfx,
+        }
+    }
+
+    /// Value of the strong coupling at `q2` used by this PDF.
+    ///
+    /// # Panics
+    ///
+    /// If the value of `q2` is negative or the PDF set doesn't tabulate the strong coupling with
+    /// `AlphaS_Type: ipol`, this method will panic.
+    fn alphas_q2(&self, q2: f64) -> f64 {
+        self.alphas
+            .as_ref()
+            .unwrap_or_else(|| {
+                panic!(
+                    "PDF set '{}' doesn't define an interpolated strong coupling",
+                    self.setname
+                )
+            })
+            .alphas_q2(q2)
+    }
+
+    fn flavors(&self) -> Vec<i32> {
+        self.flavors.clone()
+    }
+
+    fn x_min(&self) -> f64 {
+        self.x_knots[0]
+    }
+
+    fn x_max(&self) -> f64 {
+        self.x_knots[self.x_knots.len() - 1]
+    }
+
+    fn q2_min(&self) -> f64 {
+        self.q2_knots[0]
+    }
+
+    fn q2_max(&self) -> f64 {
+        self.q2_knots[self.q2_knots.len() - 1]
+    }
+
+    /// Derivative of `x * f(x)` with respect to `x` at `x` and `q2` for the given PDG ID.
+    ///
+    /// Inside of the grid this is the analytic derivative of the interpolating polynomial, outside
+    /// the extrapolation is differentiated with finite differences.
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    fn dxfx_dx(&self, id: i32, x: f64, q2: f64) -> f64 {
+        self.derivative(id, x, q2, Derivative::LogX).map_or_else(
+            || backend::dxfx_dx(self, id, x, q2),
+            |derivative| derivative / x,
+        )
+    }
+
+    /// Derivative of `x * f(x)` with respect to `ln(Q^2)` at `x` and `q2` for the given PDG ID.
+    ///
+    /// Inside of the grid this is the analytic derivative of the interpolating polynomial, outside
+    /// the extrapolation is differentiated with finite differences.
+    ///
+    /// # Panics
+    ///
+    /// If the value of either `x` or `q2` is not within proper boundaries this method will panic.
+    fn dxfx_dlnq2(&self, id: i32, x: f64, q2: f64) -> f64 {
+        self.derivative(id, x, q2, Derivative::LogQ2)
+            .unwrap_or_else(|| backend::dxfx_dlnq2(self, id, x, q2))
+    }
+}
+
+#[cfg(feature = "lhapdf")]
+fn pdfset_path(setname: &str, member: i32) -> Result<PathBuf> {
+    super::manager::pdfset_path(setname, Some(member))
+}
+
+/// Without LHAPDF, PDF sets are searched in the directories listed in `LHAPDF_DATA_PATH` and
+/// `LHAPATH`.
+#[cfg(not(feature = "lhapdf"))]
+fn pdfset_path(setname: &str, _: i32) -> Result<PathBuf> {
+    ["LHAPDF_DATA_PATH", "LHAPATH"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .flat_map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .map(|path| path.join(setname))
+        .find(|path| path.join(format!("{setname}.info")).is_file())
+        .ok_or_else(|| Error::General(format!("Info file not found for PDF set '{setname}'")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+
+    fn test_grid() -> Result<GridSet> {
+        GridSet::open(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/TestGrid"))
+    }
+
+    fn assert_close(lhs: f64, rhs: f64, tolerance: f64) {
+        assert!(
+            (lhs - rhs).abs() <= tolerance * lhs.abs().max(rhs.abs()).max(1e-300),
+            "{lhs} != {rhs}"
+        );
+    }
+
+    #[test]
+    fn interpolation_reproduces_knots() -> Result<()> {
+        let set = test_grid()?;
+        let member = set.member(1)?;
+        let pdf = NativePdf::from_set(&set, 1)?;
+
+        for subgrid in member.subgrids() {
+            for (ix, &x) in subgrid.x().iter().enumerate() {
+                for (iq, &q) in subgrid.q().iter().enumerate() {
+                    for (ifl, &pid) in subgrid.flavors().iter().enumerate() {
+                        assert_close(pdf.xfx_q2(pid, x, q * q), subgrid.value(ix, iq, ifl), 1e-14);
+                    }
+                }
+            }
+        }
+
+        assert_close(
+            pdf.xfx_q2(0, 0.01, 100.0),
+            pdf.xfx_q2(21, 0.01, 100.0),
+            1e-14,
+        );
+        assert_close(pdf.xfx_q2(5, 0.01, 100.0), 0.0, 1e-14);
+        assert_eq!(pdf.flavors(), [-3, -2, -1, 1, 2, 3, 21]);
+        assert_close(pdf.x_min(), 1e-5, 1e-14);
+        assert_close(pdf.q2_max(), 1e6, 1e-14);
+        assert_eq!(
+            format!("{pdf:?}"),
+            "NativePdf { setname: \"TestGrid\", .. }"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn continuation_extrapolation() -> Result<()> {
+        let pdf = NativePdf::from_set(&test_grid()?, 0)?;
+        let (x_min, q2_min, q2_max) = (pdf.x_min(), pdf.q2_min(), pdf.q2_max());
+
+        // the extrapolation is continuous at the boundaries of the grid
+        for pid in [-1, 2, 21] {
+            assert_close(
+                pdf.xfx_q2(pid, x_min * (1.0 - 1e-9), 100.0),
+                pdf.xfx_q2(pid, x_min, 100.0),
+                1e-6,
+            );
+            assert_close(
+                pdf.xfx_q2(pid, 0.1, q2_max * (1.0 + 1e-9)),
+                pdf.xfx_q2(pid, 0.1, q2_max),
+                1e-6,
+            );
+            assert_close(
+                pdf.xfx_q2(pid, 0.1, q2_min * (1.0 - 1e-9)),
+                pdf.xfx_q2(pid, 0.1, q2_min),
+                1e-6,
+            );
+
+            for (x, q2) in [(1e-7, 1e7), (1e-7, 1.0), (0.5, 0.5), (1e-6, 50.0)] {
+                assert!(pdf.xfx_q2(pid, x, q2).is_finite());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn derivatives() -> Result<()> {
+        let pdf = NativePdf::from_set(&test_grid()?, 0)?;
+
+        // points in the grid between the knots, and outside of it
+        for (x, q2) in [
+            (3e-5, 5.0),
+            (2e-3, 50.0),
+            (0.05, 500.0),
+            (0.4, 5e4),
+            (1e-6, 100.0),
+            (0.1, 1e7),
+        ] {
+            for pid in pdf.flavors() {
+                let scale = pdf.xfx_q2(pid, x, q2).abs();
+
+                let analytic = pdf.dxfx_dx(pid, x, q2);
+                let numeric = backend::dxfx_dx(&pdf, pid, x, q2);
+                assert!(
+                    (analytic - numeric).abs() * x <= 1e-6 * scale,
+                    "{analytic} != {numeric}"
+                );
+
+                let analytic = pdf.dxfx_dlnq2(pid, x, q2);
+                let numeric = backend::dxfx_dlnq2(&pdf, pid, x, q2);
+                assert!(
+                    (analytic - numeric).abs() <= 1e-6 * scale,
+                    "{analytic} != {numeric}"
+                );
+            }
+        }
+
+        assert_close(pdf.dxfx_dx(5, 0.1, 100.0), 0.0, 1e-14);
+        assert_close(pdf.dxfx_dlnq2(5, 0.1, 100.0), 0.0, 1e-14);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interpolated_alphas() -> Result<()> {
+        let set = test_grid()?;
+        let pdf = NativePdf::from_set(&set, 0)?;
+        let qs: Vec<f64> = set.info().get_list("AlphaS_Qs")?.unwrap();
+        let values: Vec<f64> = set.info().get_list("AlphaS_Vals")?.unwrap();
+
+        for (q, value) in qs.iter().zip(values) {
+            assert_close(pdf.alphas_q2(q * q), value, 1e-14);
+        }
+
+        // the coupling is frozen above the last knot and follows a power law below the first
+        assert_close(pdf.alphas_q2(1e8), pdf.alphas_q2(1e6), 1e-14);
+        assert!(pdf.alphas_q2(1.0) > pdf.alphas_q2(qs[0] * qs[0]));
+        assert!(pdf.alphas_q2(50.0) > pdf.alphas_q2(60.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn force_positive() -> Result<()> {
+        let mut pdf = NativePdf::from_set(&test_grid()?, 0)?;
+
+        assert_eq!(pdf.force_positive(), 0);
+        assert_close(pdf.xfx_q2(2, 1.0, 100.0), 0.0, 1e-14);
+
+        pdf.set_force_positive(2);
+
+        assert_eq!(pdf.force_positive(), 2);
+        assert_close(pdf.xfx_q2(2, 1.0, 100.0), 1e-10, 1e-14);
+
+        Ok(())
+    }
+
+    #[test]
+    fn subgrids_with_different_x_knots() -> Result<()> {
+        let flavors = vec![1, 2];
+        let subgrid = |x: Vec<f64>, q: Vec<f64>| {
+            let len = x.len() * q.len() * flavors.len();
+            SubGrid::new(x, q, flavors.clone(), vec![0.0; len])
+        };
+        let q = |q2: &[f64]| q2.iter().map(|q2: &f64| q2.sqrt()).collect();
+        let member = Member::new(
+            Info::default(),
+            vec![
+                subgrid(vec![1e-3, 1e-2, 0.1, 0.5], q(&[2.0, 10.0]))?,
+                subgrid(
+                    vec![1e-4, 1e-3, 1e-2, 0.1, 0.5, 1.0],
+                    q(&[10.0, 100.0, 1e3]),
+                )?,
+            ],
+        );
+
+        // evaluating such a member at `x = 5e-4` and `Q^2 = 5` would index below the first subgrid
+        assert_eq!(
+            NativePdf::from_member("Probe", &Info::default(), &member)
+                .unwrap_err()
+                .to_string(),
+            "subgrids 0 and 1 have different x knots"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn registered_set() -> Result<()> {
+        let knots = [(
+            vec![1e-4, 1e-3, 1e-2, 0.1, 0.5, 1.0],
+            vec![1.0, 10.0, 100.0],
+        )];
+        let xfx = |pid: i32, x: f64, _: f64| f64::from(pid) * x * (1.0 - x);
+
+        #[cfg(feature = "lhapdf")]
+        super::super::test::init();
+
+        let mut writer = super::super::PdfSetWriter::from_fn("InMemory", &knots, vec![1, 2], xfx)?;
+        writer.add_member_fn(|pid, x, q2| 2.0 * xfx(pid, x, q2))?;
+        super::super::register_set(&writer)?;
+
+        let pdf = NativePdf::with_setname_and_member("InMemory", 1)?;
+
+        assert_eq!(pdf.setname(), "InMemory");
+        assert_eq!(pdf.flavors(), [1, 2]);
+        assert_close(pdf.xfx_q2(2, 0.1, 50.0), 2.0 * xfx(2, 0.1, 50.0), 1e-12);
+        assert_eq!(
+            NativePdf::with_setname_and_member("InMemory", 2)
+                .unwrap_err()
+                .to_string(),
+            "PDF InMemory/2 is out of the member range of set InMemory"
+        );
+
+        assert!(super::super::unregister_set("InMemory")?);
+        assert!(!super::super::unregister_set("InMemory")?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "lhapdf")]
+    #[test]
+    fn compare_with_lhapdf() -> Result<()> {
+        super::super::test::init();
+
+        let setname = "NNPDF31_nlo_as_0118_luxqed";
+
+        for member in [0, 1] {
+            let lhapdf = super::super::Pdf::with_setname_and_member(setname, member)?;
+            let native = NativePdf::with_setname_and_member(setname, member)?;
+            let backends: [&dyn PdfBackend; 2] = [&lhapdf, &native];
+
+            assert_eq!(backends[0].flavors(), backends[1].flavors());
+            assert_eq!(backends[0].x_min(), backends[1].x_min());
+            assert_eq!(backends[0].q2_max(), backends[1].q2_max());
+
+            for x in [1e-10, 1e-9, 3.7e-6, 1e-3, 0.123, 0.5, 0.9] {
+                for q2 in [1.0, 1.65 * 1.65, 2.1, 10.0, 4.92 * 4.92, 8315.0, 1e8, 1e12] {
+                    for pid in native.flavors() {
+                        assert_close(
+                            backends[0].xfx_q2(pid, x, q2),
+                            backends[1].xfx_q2(pid, x, q2),
+                            1e-12,
+                        );
+                    }
+
+                    assert_close(backends[0].alphas_q2(q2), backends[1].alphas_q2(q2), 1e-12);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "lhapdf")]
+    #[test]
+    fn compare_derivatives_with_lhapdf() -> Result<()> {
+        super::super::test::init();
+
+        let setname = "NNPDF31_nlo_as_0118_luxqed";
+        let lhapdf = super::super::Pdf::with_setname_and_member(setname, 0)?;
+        let native = NativePdf::with_setname_and_member(setname, 0)?;
+
+        // finite differences of LHAPDF against the derivatives of the interpolating polynomial
+        for x in [3.7e-6, 1e-3, 0.123, 0.5] {
+            for q2 in [2.1, 10.0, 8315.0, 1e8] {
+                for pid in native.flavors() {
+                    let scale = native.xfx_q2(pid, x, q2).abs();
+
+                    assert!(
+                        (lhapdf.dxfx_dx(pid, x, q2) - native.dxfx_dx(pid, x, q2)).abs() * x
+                            <= 1e-6 * scale
+                    );
+                    assert!(
+                        (lhapdf.dxfx_dlnq2(pid, x, q2) - native.dxfx_dlnq2(pid, x, q2)).abs()
+                            <= 1e-6 * scale
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
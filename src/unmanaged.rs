@@ -1,6 +1,14 @@
-use super::Result;
 use super::ffi::{self, PDF, PDFSet};
+use super::{Error, Result};
 use cxx::{UniquePtr, let_cxx_string};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+
+/// Lock for the `static` variable holding the verbosity in C++, which isn't protected by the lock
+/// of the manager if the `managed` feature is disabled.
+static VERBOSITY: Mutex<()> = Mutex::new(());
 
 pub fn pdf_name_and_member_via_lhaid(lhaid: i32) -> Option<(String, i32)> {
     let_cxx_string!(cxx_setname = "");
@@ -17,6 +25,130 @@ pub fn pdf_name_and_member_via_lhaid(lhaid: i32) -> Option<(String, i32)> {
     }
 }
 
+pub fn find_file(target: &str) -> Option<std::path::PathBuf> {
+    let_cxx_string!(cxx_target = target);
+    let_cxx_string!(cxx_path = "");
+    ffi::find_file(&cxx_target, cxx_path.as_mut());
+
+    // LHAPDF returns an empty string if the file wasn't found
+    if cxx_path.is_empty() {
+        None
+    } else {
+        Some(std::path::PathBuf::from(
+            cxx_path.to_string_lossy().into_owned(),
+        ))
+    }
+}
+
+#[cfg(any(feature = "managed", feature = "native"))]
+pub fn pdfset_path(setname: &str, _: Option<i32>) -> Result<std::path::PathBuf> {
+    let info = find_file(&format!("{setname}/{setname}.info")).ok_or_else(|| {
+        super::Error::General(format!("Info file not found for PDF set '{setname}'"))
+    })?;
+
+    // UNWRAP: `info` has the form `.../<setname>/<setname>.info`
+    Ok(info.parent().unwrap().to_path_buf())
+}
+
+// LHAPDF reads its search paths from `LHAPDF_DATA_PATH`, or from `LHAPATH` if the former isn't
+// set, and searches its installation directory last unless the paths end with `::`. Its functions
+// `setPaths`, `pathsPrepend` and `pathsAppend` modify `LHAPDF_DATA_PATH` with `setenv`, which is
+// what we do here with `env::set_var`, because that at least synchronizes with Rust code reading
+// the environment. The following functions must therefore not be called while other threads read
+// or write the environment
+
+/// Directories that this crate added to LHAPDF's search paths.
+static DATA_PATHS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+static INIT: Once = Once::new();
+
+fn data_paths_lock() -> MutexGuard<'static, Vec<PathBuf>> {
+    // the list is consistent after every modification, even if the thread panicked afterwards
+    DATA_PATHS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn lhapdf_data_path() -> OsString {
+    env::var_os("LHAPDF_DATA_PATH")
+        .or_else(|| env::var_os("LHAPATH"))
+        .unwrap_or_default()
+}
+
+/// Put `paths` in front of LHAPDF's search paths the first time this function is called. With the
+/// `managed` feature `paths` replace the search paths of the environment instead.
+pub unsafe fn init_paths(paths: &[&Path]) {
+    INIT.call_once(|| {
+        let mut value = paths
+            .iter()
+            .map(|path| path.as_os_str())
+            .collect::<Vec<_>>()
+            // LHAPDF splits the paths at colons
+            .join(OsStr::new(":"));
+        let old = lhapdf_data_path();
+
+        if !cfg!(feature = "managed") && !old.is_empty() {
+            value.push(":");
+            value.push(old);
+        }
+
+        // SAFETY: guaranteed by the caller
+        unsafe { env::set_var("LHAPDF_DATA_PATH", value) };
+        data_paths_lock().extend(paths.iter().map(|path| path.to_path_buf()));
+    });
+}
+
+#[cfg(not(feature = "managed"))]
+pub unsafe fn init() {
+    // SAFETY: guaranteed by the caller
+    unsafe { init_paths(&[&super::memory::dir()]) };
+}
+
+/// Return an error if [`init_paths`] wasn't called yet.
+pub fn require_init() -> Result<()> {
+    if INIT.is_completed() {
+        Ok(())
+    } else {
+        Err(Error::General(
+            "LHAPDF's search paths aren't set, call `managed_lhapdf::init` first".to_owned(),
+        ))
+    }
+}
+
+pub unsafe fn paths_append(path: &Path) -> Result<()> {
+    // with the `managed` feature, `init_paths` would discard `path`
+    require_init()?;
+
+    let old = lhapdf_data_path();
+    // keep the `::` that stops LHAPDF from searching its installation directory at the end
+    let (mut value, suffix) = old
+        .to_str()
+        .and_then(|old| old.strip_suffix("::"))
+        .map_or_else(|| (old.clone(), ""), |old| (old.into(), "::"));
+
+    if !value.is_empty() {
+        value.push(":");
+    }
+
+    value.push(path);
+    value.push(suffix);
+
+    // SAFETY: guaranteed by the caller
+    unsafe { env::set_var("LHAPDF_DATA_PATH", value) };
+    data_paths_lock().push(path.to_path_buf());
+
+    Ok(())
+}
+
+pub fn data_paths() -> Vec<PathBuf> {
+    data_paths_lock().clone()
+}
+
+pub fn search_paths() -> Vec<std::path::PathBuf> {
+    ffi::search_paths()
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned().into())
+        .collect()
+}
+
 pub fn pdf_with_setname_and_member(setname: &str, member: i32) -> Result<UniquePtr<PDF>> {
     let_cxx_string!(cxx_setname = setname.to_string());
     Ok(ffi::pdf_with_setname_and_member(&cxx_setname, member)?)
@@ -28,12 +160,13 @@ pub fn pdfset_new(setname: &str) -> Result<UniquePtr<PDFSet>> {
 }
 
 pub fn set_verbosity(verbosity: i32) {
-    // this modifies a `static` variable in C++, beware of threads calling this function at the
-    // same time
+    // this modifies a `static` variable in C++
+    let _guard = VERBOSITY.lock().unwrap_or_else(PoisonError::into_inner);
     ffi::setVerbosity(verbosity);
 }
 
 pub fn verbosity() -> i32 {
     // accesses a `static` variable in C++
+    let _guard = VERBOSITY.lock().unwrap_or_else(PoisonError::into_inner);
     ffi::verbosity()
 }
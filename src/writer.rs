@@ -0,0 +1,642 @@
+//! Writer for PDF sets in the LHAPDF6 format.
+//!
+//! The files written by [`PdfSetWriter`] can be read back with the types of the
+//! [`grid`](super::grid) module, loaded by LHAPDF once they are in one of its search paths, or
+//! registered in memory.
+
+use super::grid::{Info, Member, SubGrid};
+use super::{Error, Result};
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writer for PDF sets in the LHAPDF6 format.
+///
+/// All members of a set share the same flavours and
+/// the same subgrids, whose knots are given in `x` and `Q^2`. The entries `Format`, `NumMembers`,
+/// `Flavors`, `XMin`, `XMax`, `QMin` and `QMax` of the metadata are filled in automatically.
+#[derive(Clone, Debug)]
+pub struct PdfSetWriter {
+    name: String,
+    info: Info,
+    flavors: Vec<i32>,
+    knots: Vec<(Vec<f64>, Vec<f64>)>,
+    members: Vec<Member>,
+}
+
+impl PdfSetWriter {
+    /// Constructor. Create a writer for the PDF set `name` with partons given by the PDG IDs in
+    /// `flavors`.
+    ///
+    /// # Errors
+    ///
+    /// If `name` can't be used as a directory name or `flavors` is empty or contains duplicates an
+    /// error is returned.
+    pub fn new(name: &str, flavors: Vec<i32>) -> Result<Self> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(Error::General(format!(
+                "'{name}' is not a valid PDF set name"
+            )));
+        }
+
+        let mut sorted = flavors.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        if sorted.is_empty() || sorted.len() != flavors.len() {
+            return Err(Error::General(
+                "flavours must be non-empty and unique".to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            info: Info::default(),
+            flavors,
+            knots: Vec::new(),
+            members: Vec::new(),
+        })
+    }
+
+    /// Constructor. Create a writer for the PDF set `name` whose central member tabulates
+    /// `xfx(pid, x, q2)`, the value of `x * f(x, Q^2)` for the parton with PDG ID `pid`, on the
+    /// subgrids given by the pairs of knots in `x` and `Q^2` in `knots`. Further members can be
+    /// added with [`PdfSetWriter::add_member_fn`].
+    ///
+    /// With the `lhapdf` or `native` feature the set is registered with
+    /// [`register_set`](super::register_set), so that it can be loaded by its name right away.
+    /// Members or metadata added afterwards are only visible after registering the set again.
+    ///
+    /// # Errors
+    ///
+    /// If the name, flavours or knots are invalid, `xfx` returns values that aren't finite, or the
+    /// set could not be registered an error is returned.
+    pub fn from_fn(
+        name: &str,
+        knots: &[(Vec<f64>, Vec<f64>)],
+        flavors: Vec<i32>,
+        xfx: impl Fn(i32, f64, f64) -> f64,
+    ) -> Result<Self> {
+        let mut writer = Self::new(name, flavors)?;
+
+        for (x, q2) in knots {
+            writer.add_subgrid(x.clone(), q2.clone())?;
+        }
+
+        writer.add_member_fn(xfx)?;
+
+        #[cfg(any(feature = "lhapdf", feature = "native"))]
+        super::register_set(&writer)?;
+
+        Ok(writer)
+    }
+
+    /// Return the name of the PDF set.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the metadata written to the `.info` file.
+    #[must_use]
+    pub const fn info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Return the metadata written to the `.info` file for modification.
+    pub const fn info_mut(&mut self) -> &mut Info {
+        &mut self.info
+    }
+
+    /// Set the description of the PDF set, `SetDesc`.
+    pub fn set_description(&mut self, description: &str) {
+        self.info
+            .set("SetDesc", format!("\"{}\"", description.replace('"', "'")));
+    }
+
+    /// Set the LHAID of the first member, `SetIndex`.
+    pub fn set_lhaid(&mut self, lhaid: i32) {
+        self.info.set("SetIndex", lhaid);
+    }
+
+    /// Set the version of the data, `DataVersion`.
+    pub fn set_data_version(&mut self, version: i32) {
+        self.info.set("DataVersion", version);
+    }
+
+    /// Set the type of the PDF uncertainties, `ErrorType`, for example `replicas`, `hessian` or
+    /// `symmhessian`. This also determines the `PdfType` of the members.
+    pub fn set_error_type(&mut self, error_type: &str) {
+        self.info.set("ErrorType", error_type);
+    }
+
+    /// Set the perturbative QCD order of the PDFs, `OrderQCD`, where 0 is leading order.
+    pub fn set_order_qcd(&mut self, order: i32) {
+        self.info.set("OrderQCD", order);
+    }
+
+    /// Tabulate the strong coupling with the values `alphas` at the scales `q`, which is used with
+    /// `AlphaS_Type: ipol`. Repeating a value of `q` separates subgrids, for instance at flavour
+    /// thresholds.
+    ///
+    /// # Errors
+    ///
+    /// If `q` and `alphas` don't have the same length an error is returned.
+    pub fn set_alphas_ipol(&mut self, q: &[f64], alphas: &[f64]) -> Result<()> {
+        if q.len() != alphas.len() {
+            return Err(Error::General(format!(
+                "{} scales given for {} values of the strong coupling",
+                q.len(),
+                alphas.len()
+            )));
+        }
+
+        self.info.set("AlphaS_Type", "ipol");
+        self.info.set_list("AlphaS_Qs", q);
+        self.info.set_list("AlphaS_Vals", alphas);
+
+        Ok(())
+    }
+
+    /// Set the metadata entry `key` to `value`.
+    pub fn set_entry(&mut self, key: &str, value: impl Display) {
+        self.info.set(key, value);
+    }
+
+    /// Add a subgrid with the knots `x` and `q2`. Subgrids must be added in order of increasing
+    /// `Q^2`, each one starting at the last knot of the previous one, and before any member. Like
+    /// in LHAPDF, all subgrids have the same knots in `x`.
+    ///
+    /// # Errors
+    ///
+    /// If members were already added, the knots are invalid or `x` differs from the knots of the
+    /// first subgrid an error is returned.
+    pub fn add_subgrid(&mut self, x: Vec<f64>, q2: Vec<f64>) -> Result<()> {
+        if !self.members.is_empty() {
+            return Err(Error::General(
+                "subgrids must be added before the members".to_owned(),
+            ));
+        }
+
+        if q2.iter().any(|&q2| q2 <= 0.0) {
+            return Err(Error::General("Q^2 knots are not positive".to_owned()));
+        }
+
+        if self.knots.first().is_some_and(|(first_x, _)| {
+            first_x.len() != x.len() || first_x.iter().zip(&x).any(|(a, b)| a.total_cmp(b).is_ne())
+        }) {
+            return Err(Error::General(format!(
+                "subgrid {} has different x knots than the first subgrid",
+                self.knots.len()
+            )));
+        }
+
+        let mut knots = self.knots.clone();
+        knots.push((x, q2));
+
+        // check the knots with a member whose values are all zero
+        let subgrids = knots
+            .iter()
+            .map(|(x, q2)| self.subgrid(x, q2, vec![0.0; x.len() * q2.len() * self.flavors.len()]))
+            .collect::<Result<_>>()?;
+        Member::new(Info::default(), subgrids).validate()?;
+
+        self.knots = knots;
+
+        Ok(())
+    }
+
+    fn subgrid(&self, x: &[f64], q2: &[f64], values: Vec<f64>) -> Result<SubGrid> {
+        SubGrid::new(
+            x.to_vec(),
+            q2.iter().map(|q2| q2.sqrt()).collect(),
+            self.flavors.clone(),
+            values,
+        )
+    }
+
+    /// Add a member with the values `x * f(x, Q^2)` given for each subgrid in `values`. For every
+    /// subgrid the values are ordered with the flavour index running fastest, followed by the
+    /// `Q^2` index and the `x` index. Members are numbered in the order they are added, the
+    /// first one being the central member.
+    ///
+    /// # Errors
+    ///
+    /// If the number of subgrids or values doesn't match the knots and flavours, or a value isn't
+    /// finite an error is returned.
+    pub fn add_member(&mut self, values: Vec<Vec<f64>>) -> Result<()> {
+        if values.len() != self.knots.len() {
+            return Err(Error::General(format!(
+                "{} subgrids were defined, but values for {} were given",
+                self.knots.len(),
+                values.len()
+            )));
+        }
+
+        let subgrids = self
+            .knots
+            .iter()
+            .zip(values)
+            .map(|((x, q2), values)| self.subgrid(x, q2, values))
+            .collect::<Result<_>>()?;
+        let member = Member::new(Info::default(), subgrids);
+        member.validate()?;
+
+        self.members.push(member);
+
+        Ok(())
+    }
+
+    /// Add a member by tabulating `xfx(pid, x, q2)`, the value of `x * f(x, Q^2)` for the parton
+    /// with PDG ID `pid`, on the knots of every subgrid.
+    ///
+    /// # Errors
+    ///
+    /// If `xfx` returns values that aren't finite an error is returned.
+    pub fn add_member_fn(&mut self, xfx: impl Fn(i32, f64, f64) -> f64) -> Result<()> {
+        self.add_member(self.tabulate(xfx))
+    }
+
+    /// Evaluate `xfx` on the knots of every subgrid, in the order expected by
+    /// [`PdfSetWriter::add_member`].
+    pub(crate) fn tabulate(&self, xfx: impl Fn(i32, f64, f64) -> f64) -> Vec<Vec<f64>> {
+        let mut values = Vec::with_capacity(self.knots.len());
+
+        for (x, q2) in &self.knots {
+            let mut subgrid_values = Vec::with_capacity(x.len() * q2.len() * self.flavors.len());
+
+            for &x in x {
+                for &q2 in q2 {
+                    subgrid_values.extend(self.flavors.iter().map(|&pid| xfx(pid, x, q2)));
+                }
+            }
+
+            values.push(subgrid_values);
+        }
+
+        values
+    }
+
+    /// Return the number of members added so far.
+    #[must_use]
+    pub const fn num_members(&self) -> usize {
+        self.members.len()
+    }
+
+    #[cfg(any(feature = "lhapdf", feature = "native", test))]
+    pub(crate) fn members(&self) -> &[Member] {
+        &self.members
+    }
+
+    pub(crate) fn set_info(&self) -> Result<Info> {
+        let (Some((first_x, first_q2)), Some((_, last_q2))) =
+            (self.knots.first(), self.knots.last())
+        else {
+            return Err(Error::General(format!(
+                "PDF set '{}' doesn't have any subgrids",
+                self.name
+            )));
+        };
+
+        if self.members.is_empty() {
+            return Err(Error::General(format!(
+                "PDF set '{}' doesn't have any members",
+                self.name
+            )));
+        }
+
+        let mut info = self.info.clone();
+        info.set("Format", "lhagrid1");
+        info.set("NumMembers", self.members.len());
+        info.set_list("Flavors", &self.flavors);
+        // all subgrids have the same ascending knots in `x`, at least two of them
+        info.set("XMin", format!("{:e}", first_x[0]));
+        info.set("XMax", format!("{:e}", first_x[first_x.len() - 1]));
+        info.set("QMin", format!("{:e}", first_q2[0].sqrt()));
+        info.set("QMax", format!("{:e}", last_q2[last_q2.len() - 1].sqrt()));
+
+        Ok(info)
+    }
+
+    fn member_contents(&self, index: usize) -> String {
+        let pdf_type = if index == 0 {
+            "central"
+        } else if self.info.get("ErrorType") == Some("replicas") {
+            "replica"
+        } else {
+            "error"
+        };
+        let join = |values: &[f64]| {
+            values
+                .iter()
+                .map(|value| format!("{value:e}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut contents = format!("PdfType: {pdf_type}\nFormat: lhagrid1\n---\n");
+
+        for subgrid in self.members[index].subgrids() {
+            contents.push_str(&join(subgrid.x()));
+            contents.push('\n');
+            contents.push_str(&join(subgrid.q()));
+            contents.push('\n');
+            contents.push_str(
+                &subgrid
+                    .flavors()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+            contents.push('\n');
+
+            for row in subgrid.values().chunks(subgrid.flavors().len()) {
+                contents.push_str(&join(row));
+                contents.push('\n');
+            }
+
+            contents.push_str("---\n");
+        }
+
+        contents
+    }
+
+    /// Write the PDF set into the directory `<dir>/<name>`, replacing a set with the same name.
+    /// The files are written into a temporary directory first, which is then renamed, so that a
+    /// partially written set is never visible. If the set can't be moved into place, a previous
+    /// version is restored. Return the directory of the set.
+    ///
+    /// A set that LHAPDF already loaded under the same name keeps using the old metadata until the
+    /// program is restarted.
+    ///
+    /// # Errors
+    ///
+    /// If the set doesn't have subgrids or members, or the files could not be written an error is
+    /// returned.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        let info = self.set_info()?;
+        let name = &self.name;
+        // use a different prefix than the manager, which downloads into `.<name>.<pid>`
+        let tmp = dir.join(format!(".{name}.writer.{}", std::process::id()));
+        let old = tmp.join(format!("{name}.old"));
+        let target = dir.join(name);
+
+        let result = fs::create_dir_all(tmp.join(name))
+            .and_then(|()| {
+                fs::write(
+                    tmp.join(name).join(format!("{name}.info")),
+                    info.to_string(),
+                )
+            })
+            .and_then(|()| {
+                (0..self.members.len()).try_for_each(|index| {
+                    fs::write(
+                        tmp.join(name).join(format!("{name}_{index:04}.dat")),
+                        self.member_contents(index),
+                    )
+                })
+            })
+            .map_err(Error::from)
+            .and_then(|()| {
+                if target.exists() {
+                    fs::rename(&target, &old)?;
+                }
+
+                if let Err(err) = fs::rename(tmp.join(name), &target) {
+                    // put the previous version back, otherwise it's removed with `tmp`
+                    if old.exists() {
+                        fs::rename(&old, &target).map_err(|restore_err| {
+                            Error::General(format!(
+                                "could not write PDF set '{name}': '{err}', and could not restore \
+                                 its previous version from '{}': '{restore_err}'",
+                                old.display()
+                            ))
+                        })?;
+                    }
+
+                    return Err(err.into());
+                }
+
+                Ok(())
+            });
+
+        // remove what's left over, including the previous version of the set - unless it couldn't
+        // be restored
+        if !old.exists() || result.is_ok() {
+            // ignore errors when cleaning up after a failure, the original one is more important
+            let cleanup = fs::remove_dir_all(&tmp);
+
+            if result.is_ok() {
+                cleanup?;
+            }
+        }
+
+        result.map(|()| target)
+    }
+
+    /// Write the PDF set into the directory where `managed-lhapdf` downloads PDF sets to, see
+    /// [`Config::lhapdf_data_path_write`](super::Config::lhapdf_data_path_write), so that it can
+    /// be loaded by its name. Return the directory of the set.
+    ///
+    /// # Errors
+    ///
+    /// If no download directory is configured, or [`PdfSetWriter::write`] fails an error is
+    /// returned.
+    #[cfg(feature = "managed")]
+    pub fn write_to_data_path(&self) -> Result<PathBuf> {
+        let dir = super::Config::get()
+            .lhapdf_data_path_write()
+            .ok_or_else(|| {
+                Error::General("can not write PDF sets without `lhapdf_data_path_write`".to_owned())
+            })?;
+
+        self.write(dir)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::grid::GridSet;
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("managed-lhapdf-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let flavors = vec![-1, 21, 1];
+        let x = vec![1e-4, 1e-3, 1e-2, 0.1, 0.5, 1.0];
+        let q2s = [vec![2.0, 4.0, 10.0], vec![10.0, 100.0, 1e4, 1e6]];
+        let mut writer = PdfSetWriter::new("RoundTrip", flavors.clone())?;
+
+        writer.set_description("set written by \"round_trip\"");
+        writer.set_lhaid(900_000);
+        writer.set_error_type("replicas");
+        writer.set_alphas_ipol(&[1.0, 10.0, 100.0], &[0.35, 0.18, 0.11])?;
+
+        for q2 in &q2s {
+            writer.add_subgrid(x.clone(), q2.clone())?;
+        }
+
+        let values = |scale: f64| {
+            q2s.iter()
+                .map(|q2| {
+                    std::iter::successors(Some(scale / 3.0), |value| Some(value + 0.1))
+                        .take(x.len() * q2.len() * flavors.len())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        writer.add_member(values(1.0))?;
+        writer.add_member(values(2.0))?;
+
+        let path = writer.write(&temp_dir("writer"))?;
+        let set = GridSet::open(&path)?;
+
+        set.validate()?;
+        assert_eq!(set.name(), "RoundTrip");
+        assert_eq!(set.info().num_members()?, 2);
+        assert_eq!(set.info().flavors()?, flavors);
+        assert_eq!(set.info().get_as("SetIndex")?, Some(900_000));
+        assert_eq!(
+            set.info().get_as::<String>("SetDesc")?.as_deref(),
+            Some("set written by 'round_trip'")
+        );
+        assert_eq!(set.info().get_as("QMin")?, Some(2.0f64.sqrt()));
+        assert_eq!(
+            set.info().get_list("AlphaS_Vals")?,
+            Some(vec![0.35, 0.18, 0.11])
+        );
+
+        let member = set.member(1)?;
+
+        assert_eq!(member.info().get("PdfType"), Some("replica"));
+        assert_eq!(member.subgrids().len(), 2);
+
+        for (subgrid, values) in member.subgrids().iter().zip(values(2.0)) {
+            assert_eq!(subgrid.x(), x);
+            assert_eq!(subgrid.values(), values);
+        }
+
+        // rewriting replaces the set
+        writer.set_data_version(2);
+        writer.write(path.parent().unwrap())?;
+
+        assert_eq!(GridSet::open(&path)?.info().data_version()?, Some(2));
+
+        fs::remove_dir_all(path.parent().unwrap())?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn tabulate_function() -> Result<()> {
+        let x = vec![1e-4, 1e-3, 1e-2, 0.1, 0.5, 1.0];
+        let knots = [
+            (x.clone(), vec![2.0, 10.0]),
+            (x.clone(), vec![10.0, 100.0, 1e3]),
+            (x, vec![1e3, 1e4]),
+        ];
+        let xfx = |pid: i32, x: f64, q2: f64| f64::from(pid) * x * (1.0 - x) * q2.ln();
+
+        // `from_fn` registers the set, which LHAPDF must be able to find
+        #[cfg(feature = "lhapdf")]
+        super::super::test::init();
+
+        // subgrids with different knots in `x` can't be evaluated by LHAPDF
+        assert_eq!(
+            PdfSetWriter::from_fn(
+                "Tabulated",
+                &[
+                    (vec![1e-3, 1e-2, 0.1, 0.5], vec![2.0, 10.0]),
+                    (knots[1].0.clone(), vec![10.0, 100.0, 1e3]),
+                ],
+                vec![1, 2],
+                xfx
+            )
+            .unwrap_err()
+            .to_string(),
+            "subgrid 1 has different x knots than the first subgrid"
+        );
+
+        let mut writer = PdfSetWriter::from_fn("Tabulated", &knots, vec![1, 2], xfx)?;
+        writer.add_member_fn(|pid, x, q2| 2.0 * xfx(pid, x, q2))?;
+
+        assert_eq!(writer.num_members(), 2);
+
+        let path = writer.write(&temp_dir("from-fn"))?;
+        let set = GridSet::open(&path)?;
+
+        assert_eq!(set.info().get("XMin"), Some("1e-4"));
+        assert_eq!(set.info().get("XMax"), Some("1e0"));
+
+        for (index, scale) in [(0, 1.0), (1, 2.0)] {
+            let member = set.member(index)?;
+            let subgrid = &member.subgrids()[1];
+
+            assert_eq!(subgrid.q().len(), 3);
+            assert!(
+                (subgrid.value(3, 1, 1) / (scale * xfx(2, 0.1, 100.0)) - 1.0).abs() < 1e-14,
+                "member {index}"
+            );
+        }
+
+        fs::remove_dir_all(path.parent().unwrap())?;
+        #[cfg(any(feature = "lhapdf", feature = "native"))]
+        assert!(super::super::unregister_set("Tabulated")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_input() -> Result<()> {
+        assert_eq!(
+            PdfSetWriter::new("../escape", vec![21])
+                .unwrap_err()
+                .to_string(),
+            "'../escape' is not a valid PDF set name"
+        );
+
+        let mut writer = PdfSetWriter::new("Invalid", vec![21])?;
+
+        assert_eq!(
+            writer.write(&temp_dir("invalid")).unwrap_err().to_string(),
+            "PDF set 'Invalid' doesn't have any subgrids"
+        );
+
+        writer.add_subgrid(vec![0.1, 0.2], vec![1.0, 2.0])?;
+
+        assert_eq!(
+            writer
+                .add_subgrid(vec![0.1, 0.2], vec![3.0, 4.0])
+                .unwrap_err()
+                .to_string(),
+            "subgrids 0 and 1 don't join at the same Q"
+        );
+        assert_eq!(
+            writer
+                .add_member(vec![vec![1.0; 3]])
+                .unwrap_err()
+                .to_string(),
+            "subgrid with 2 x knots, 2 Q knots and 1 flavours needs 4 values, found 3"
+        );
+
+        writer.add_member(vec![vec![1.0; 4]])?;
+
+        assert_eq!(
+            writer
+                .add_subgrid(vec![0.1, 0.2], vec![2.0, 4.0])
+                .unwrap_err()
+                .to_string(),
+            "subgrids must be added before the members"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "lhapdf")]
+
+// `add_data_path` modifies the environment, which must not happen while other threads read it.
+// This test therefore has a process of its own and must stay the only test in this file
+
+use managed_lhapdf::{add_data_path, data_paths, lhapdf_paths};
+
+#[test]
+fn check_add_data_path() -> managed_lhapdf::Result<()> {
+    let path = std::env::temp_dir().join("managed-lhapdf-add-data-path");
+
+    // SAFETY: see the comment at the top of this file
+    assert_eq!(
+        unsafe { add_data_path(&path) }.unwrap_err().to_string(),
+        "LHAPDF's search paths aren't set, call `managed_lhapdf::init` first"
+    );
+
+    // SAFETY: see the comment at the top of this file
+    unsafe { managed_lhapdf::init() };
+    // SAFETY: see the comment at the top of this file
+    unsafe { add_data_path(&path) }?;
+
+    // LHAPDF searches its installation directory last
+    assert!(lhapdf_paths().contains(&path));
+    assert_eq!(
+        data_paths().first(),
+        Some(&std::env::temp_dir().join(format!("managed-lhapdf-{}", std::process::id())))
+    );
+    assert_eq!(data_paths().last(), Some(&path));
+
+    Ok(())
+}